@@ -1,25 +1,73 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use reqwest::Url;
-use std::error;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::{
+    borrow::Cow,
+    error,
+    path::PathBuf,
+    sync::{mpsc, Arc},
+    time::Instant,
+};
 use strum::IntoEnumIterator;
 
+use tokio::task::JoinHandle;
 use tui_menu::{MenuItem, MenuState};
 use tui_tree_widget::TreeItem;
 
 use crate::{
+    assertion::{self, AssertionResult},
+    capture,
+    clipboard::Clipboard,
+    collection::{Collection, CollectionItem, Header, SavedRequest, Scripts},
     component::{
+        clientbar::ClientBar,
+        cookiebar::CookieBar,
+        palettebar::{Command, Entry, PaletteBar},
         requestbar::{RequestBar, RequestMenu},
-        responsebar::ResponseBar,
+        responsebar::{ResponseBar, ResponseMenu},
+        runnerbar::RunnerBar,
         sidebar::SideBar,
         tabbar::TabBar,
         urlbar::{InputMode, Method, UrlBar},
     },
+    config::{self, Config, LayoutState, Orientation},
+    cookies::CookieJar,
+    download::{self, Progress},
+    editor::{self, Edit, Target},
+    environment::{substitute, Environment, Variables},
+    event::Event,
+    http::{self, ClientSettings, HttpClient, Response},
     items::{Item, StatefulTree},
+    keymap::{Action, Context, Keymap},
+    mouse::{self, Areas},
+    runner::{self, RunOptions, Runner},
+    theme::Theme,
+    vim::Vim,
 };
 
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
+/// Outcome of a request sent in the background, with the variables and script logs it left.
+type RequestOutcome = (Result<Option<Response>, String>, Variables, Vec<String>);
+
+/// A request being sent in the background.
+#[derive(Debug)]
+pub struct PendingRequest {
+    pub progress: Arc<Progress>,
+    pub started: Instant,
+    handle: JoinHandle<RequestOutcome>,
+}
+
+/// How long a notification stays in the status bar, in ticks.
+const NOTIFICATION_TICKS: u16 = 16;
+
+/// A message shown in the status bar for a few ticks.
+#[derive(Debug)]
+pub struct Notification {
+    pub message: String,
+    pub error: bool,
+    pub ticks: u16,
+}
+
 /// Application.
 #[derive(Debug)]
 pub struct App {
@@ -31,6 +79,33 @@ pub struct App {
     pub urlbar: UrlBar,
     pub requestbar: RequestBar,
     pub responsebar: ResponseBar,
+    pub collection: Collection,
+    pub collection_path: Option<PathBuf>,
+    pub environment: Option<usize>,
+    pub runner: Option<RunnerBar>,
+    /// Output of request scripts.
+    pub console: Vec<String>,
+    pub http: HttpClient,
+    pub cookies: Option<CookieBar>,
+    pub config: Config,
+    pub client_settings: Option<ClientBar>,
+    pub pending: Option<PendingRequest>,
+    pub clipboard: Clipboard,
+    pub areas: Areas,
+    /// Whether the border between the request and response panes is being dragged.
+    pub dragging: bool,
+    pub keymap: Keymap,
+    /// Text waiting to be opened in the external editor by the main loop.
+    pub editing: Option<Edit>,
+    pub palette: Option<PaletteBar>,
+    /// First line shown in the help popup.
+    pub help_scroll: u16,
+    pub theme: Theme,
+    pub notification: Option<Notification>,
+    /// Layout as last loaded or saved.
+    pub saved_layout: LayoutState,
+    /// Wakes the main loop when a request sent in the background finishes.
+    pub waker: Option<mpsc::Sender<Event>>,
 }
 
 #[derive(Debug, Default, strum::Display, strum::EnumIter, PartialEq)]
@@ -49,6 +124,12 @@ pub enum Selected {
 pub struct Settings {
     pub show_sidebar: bool,
     pub show_help: bool,
+    pub show_console: bool,
+    /// Percentage of the lower panes given to the request pane.
+    pub split: u16,
+    pub orientation: Orientation,
+    /// Whether the focused pane is shown alone.
+    pub maximized: bool,
 }
 
 impl Default for Settings {
@@ -56,6 +137,10 @@ impl Default for Settings {
         Self {
             show_sidebar: true,
             show_help: false,
+            show_console: false,
+            split: 50,
+            orientation: Orientation::Auto,
+            maximized: false,
         }
     }
 }
@@ -95,6 +180,10 @@ impl Default for App {
             settings: Settings {
                 show_sidebar: true,
                 show_help: false,
+                show_console: false,
+                split: 50,
+                orientation: Orientation::Auto,
+                maximized: false,
             },
             tabs: TabBar { selected: 0, tabs },
             urlbar: UrlBar {
@@ -109,14 +198,40 @@ impl Default for App {
                         .map(|m| MenuItem::item(m.to_string(), m))
                         .collect(),
                 )]),
+                vim: Vim::default(),
             },
             requestbar: RequestBar {
                 body: String::new(),
                 request_menu: RequestMenu::Params,
+                ..Default::default()
             },
             responsebar: ResponseBar {
                 body: String::new(),
+                response: None,
+                response_menu: ResponseMenu::Body,
+                ..Default::default()
             },
+            collection: Collection::default(),
+            collection_path: None,
+            environment: None,
+            runner: None,
+            console: Vec::new(),
+            http: HttpClient::default(),
+            cookies: None,
+            config: Config::default(),
+            client_settings: None,
+            pending: None,
+            clipboard: Clipboard::default(),
+            areas: Areas::default(),
+            dragging: false,
+            keymap: Keymap::default(),
+            editing: None,
+            palette: None,
+            help_scroll: 0,
+            theme: Theme::default(),
+            notification: None,
+            saved_layout: LayoutState::default(),
+            waker: None,
         }
     }
 }
@@ -127,8 +242,106 @@ impl App {
         Self::default()
     }
 
+    /// Constructs a new instance of [`App`] showing the given collection.
+    pub fn with_collection(collection: Collection, path: PathBuf) -> Self {
+        let mut tree = StatefulTree::with_items(collection.tree());
+        tree.first();
+
+        let mut app = Self {
+            sidebar: SideBar {
+                size: 25,
+                selected: 0,
+                tree,
+            },
+            tabs: TabBar::default(),
+            environment: (!collection.environments.is_empty()).then_some(0),
+            collection,
+            collection_path: Some(path),
+            ..Self::default()
+        };
+
+        if let Err(err) = app.load_environment() {
+            app.console.push(err.to_string());
+        }
+        app
+    }
+
     /// Handles the tick event of the terminal.
-    pub fn tick(&self) {}
+    pub async fn tick(&mut self) {
+        if let Some(notification) = &mut self.notification {
+            notification.ticks = notification.ticks.saturating_sub(1);
+            if notification.ticks == 0 {
+                self.notification = None;
+            }
+        }
+
+        self.poll().await;
+    }
+
+    /// Takes the outcome of the requests sent in the background, and sends the next request of
+    /// a run.
+    pub async fn poll(&mut self) {
+        if let Some(runnerbar) = &mut self.runner {
+            if runnerbar
+                .pending
+                .as_ref()
+                .is_some_and(|pending| pending.is_finished())
+            {
+                if let Some(pending) = runnerbar.pending.take() {
+                    match pending.await {
+                        Ok(outcome) => {
+                            runnerbar.runner.finish(outcome);
+                        }
+                        Err(err) => runnerbar.message = Some(err.to_string()),
+                    }
+                }
+            }
+
+            if runnerbar.pending.is_none() {
+                if let Some(step) = runnerbar.runner.prepare() {
+                    let waker = self.waker.clone();
+                    runnerbar.pending = Some(tokio::spawn(async move {
+                        let outcome = step.await;
+                        wake(waker);
+                        outcome
+                    }));
+                }
+            }
+        }
+
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| pending.handle.is_finished())
+        {
+            if let Some(pending) = self.pending.take() {
+                match pending.handle.await {
+                    Ok(outcome) => self.finish_request(outcome),
+                    Err(err) => self.responsebar.body = err.to_string(),
+                }
+            }
+        }
+    }
+
+    /// Shows a message in the status bar for a while.
+    pub fn notify(&mut self, message: impl Into<String>) {
+        self.notification = Some(Notification {
+            message: message.into(),
+            error: false,
+            ticks: NOTIFICATION_TICKS,
+        });
+    }
+
+    /// Shows an error in the status bar for a while, keeping it in the console.
+    pub fn notify_error(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        self.console.push(message.clone());
+        self.notification = Some(Notification {
+            message,
+            error: true,
+            ticks: NOTIFICATION_TICKS,
+        });
+    }
 
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
@@ -150,55 +363,916 @@ impl App {
         }
     }
 
-    pub async fn request(&mut self) {
-        let client = reqwest::Client::new();
+    /// Widens or narrows the sidebar by some columns, showing it if it was hidden.
+    pub fn resize_sidebar(&mut self, columns: i16) {
+        self.settings.show_sidebar = true;
+        self.sidebar.size = self
+            .sidebar
+            .size
+            .saturating_add_signed(columns)
+            .clamp(10, 80);
+    }
+
+    /// Moves the border between the request and response panes by some percents.
+    pub fn resize_split(&mut self, percents: i16) {
+        self.settings.split = self
+            .settings
+            .split
+            .saturating_add_signed(percents)
+            .clamp(10, 90);
+    }
+
+    /// Whether the focused pane is shown alone, which only the sidebar, request and response
+    /// panes can be.
+    pub fn maximized(&self) -> bool {
+        self.settings.maximized
+            && matches!(
+                self.selected,
+                Selected::Sidebar
+                    | Selected::RequestTab
+                    | Selected::Requestbar
+                    | Selected::Responsebar
+            )
+    }
+
+    /// The current pane sizes and arrangement.
+    pub fn layout(&self) -> LayoutState {
+        LayoutState {
+            show_sidebar: self.settings.show_sidebar,
+            sidebar: self.sidebar.size,
+            split: self.settings.split,
+            orientation: self.settings.orientation,
+        }
+    }
+
+    fn set_layout(&mut self, layout: &LayoutState) {
+        self.settings.show_sidebar = layout.show_sidebar;
+        self.sidebar.size = layout.sidebar.clamp(10, 80);
+        self.settings.split = layout.split.clamp(10, 90);
+        self.settings.orientation = layout.orientation;
+    }
+
+    /// Restores the layout saved when the application last quit.
+    pub fn load_layout(&mut self) {
+        match LayoutState::load() {
+            Ok(layout) => {
+                self.set_layout(&layout);
+                self.saved_layout = layout;
+            }
+            Err(err) => self.console.push(format!("layout: {}", err)),
+        }
+    }
+
+    /// Writes the layout to its file when it changed since it was loaded.
+    ///
+    /// This runs after the terminal was restored, so failures only go to the log.
+    pub fn save_layout(&mut self) {
+        let layout = self.layout();
+        if layout == self.saved_layout {
+            return;
+        }
+        match layout.save() {
+            Ok(()) => self.saved_layout = layout,
+            Err(err) => tracing::error!("failed to save the layout: {}", err),
+        }
+    }
+
+    /// Variables of the active environment.
+    pub fn variables(&self) -> Variables {
+        self.environment
+            .and_then(|i| self.collection.environments.get(i))
+            .map(|env| env.variables.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the variables of the active environment, creating one if a variable was set
+    /// while there is none.
+    pub fn set_variables(&mut self, variables: Variables) {
+        if variables == self.variables() {
+            return;
+        }
+
+        // Named like the cookie jar used without an environment, so the same jar stays valid.
+        if self.environment.is_none() {
+            self.collection.environments.push(Environment {
+                name: self.environment_name(),
+                ..Default::default()
+            });
+            self.environment = Some(self.collection.environments.len() - 1);
+        }
+
+        if let Some(env) = self
+            .environment
+            .and_then(|i| self.collection.environments.get_mut(i))
+        {
+            env.variables = variables;
+        }
+    }
+
+    /// Makes another environment the active one, with its own cookies and client settings.
+    pub fn switch_environment(&mut self, index: usize) {
+        if index >= self.collection.environments.len() || self.environment == Some(index) {
+            return;
+        }
+
+        // The jar and client of the environment are only replaced once they loaded.
+        self.save_cookies();
+        let previous = self.environment.replace(index);
+        match self.load_environment() {
+            Ok(()) => self.notify(format!("environment: {}", self.environment_name())),
+            Err(err) => {
+                self.environment = previous;
+                self.notify_error(err.to_string());
+            }
+        }
+    }
+
+    pub fn environment_name(&self) -> String {
+        self.environment
+            .and_then(|i| self.collection.environments.get(i))
+            .map(|env| env.name.clone())
+            .unwrap_or_else(|| String::from("default"))
+    }
+
+    /// File the cookies of the active environment persist to, only for collections opened from disk.
+    fn cookie_jar_path(&self) -> Option<PathBuf> {
+        self.collection_path.as_ref()?;
+        CookieJar::path(&self.collection.name, &self.environment_name())
+    }
+
+    /// Client settings of the config file, with the proxy and TLS settings of the active environment.
+    pub fn client_settings(&self) -> ClientSettings {
+        let mut settings = self.config.client.clone();
+        if let Some(env) = self
+            .environment
+            .and_then(|i| self.collection.environments.get(i))
+        {
+            settings.apply_environment(env);
+        }
+        settings
+    }
 
-        let method = match self.urlbar.method {
-            Method::Get => reqwest::Method::GET,
-            Method::Post => reqwest::Method::POST,
-            Method::Put => reqwest::Method::PUT,
-            Method::Delete => reqwest::Method::DELETE,
-            Method::Patch => reqwest::Method::PATCH,
-            Method::Head => reqwest::Method::HEAD,
-            Method::Options => reqwest::Method::OPTIONS,
+    /// Rebuilds the client around the cookie jar and settings of the active environment.
+    pub fn load_environment(&mut self) -> AppResult<()> {
+        let jar = match self.cookie_jar_path() {
+            Some(path) => CookieJar::load(&path)?,
+            None => CookieJar::default(),
         };
+        self.http = HttpClient::new(Arc::new(jar), self.client_settings())?;
+        Ok(())
+    }
+
+    /// Uses a new configuration, rebuilding the client when its settings changed.
+    pub fn set_config(&mut self, config: Config) -> AppResult<()> {
+        let (keymap, mut problems) = Keymap::with_config(&config.keys);
+        problems.extend(keymap.conflicts());
+        let mut problems = problems
+            .into_iter()
+            .map(|problem| format!("keys: {}", problem))
+            .collect::<Vec<_>>();
+        self.keymap = keymap;
+
+        let (theme, theme_problems) = Theme::with_config(&config);
+        problems.extend(
+            theme_problems
+                .into_iter()
+                .map(|problem| format!("theme: {}", problem)),
+        );
+        self.theme = theme;
+
+        if !problems.is_empty() {
+            self.settings.show_console = true;
+        }
+        self.console.extend(problems);
 
-        let url = match Url::parse(&self.urlbar.text) {
-            Ok(url) => url,
-            Err(_) => Url::parse(&format!("https://{}", &self.urlbar.text)).unwrap(),
+        self.config = config;
+        let settings = self.client_settings();
+        if settings != self.http.settings {
+            self.http = self.http.with_settings(settings)?;
+        }
+        Ok(())
+    }
+
+    pub fn save_cookies(&mut self) {
+        if let Some(path) = self.cookie_jar_path() {
+            if let Err(err) = self.http.cookies.save(&path) {
+                self.console.push(err.to_string());
+            }
+        }
+    }
+
+    /// Collection path of the request opened in the selected tab.
+    pub fn tab_path(&self) -> Vec<usize> {
+        self.tabs
+            .tabs
+            .get(self.tabs.selected)
+            .map(|tab| tab.borrow().path.clone())
+            .unwrap_or_default()
+    }
+
+    /// Loads the request of the selected tab into the url and request bars.
+    pub fn load_tab(&mut self) {
+        if let Some(CollectionItem::Request(request)) = self.collection.get(&self.tab_path()) {
+            let request = request.clone();
+            self.urlbar.load(&request);
+            self.requestbar.body = request.body;
+            self.requestbar.tests = request
+                .assertions
+                .iter()
+                .map(|assertion| assertion.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.requestbar.captures = request
+                .captures
+                .iter()
+                .map(|capture| capture.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.requestbar.pre_request = request.scripts.pre_request;
+            self.requestbar.post_response = request.scripts.post_response;
+            self.requestbar.disable_cookies = request.disable_cookies;
+            self.responsebar.recent_filters = request.filters;
+            self.responsebar.set_filter(String::new());
+            self.requestbar.test_results.clear();
+            self.requestbar.cursor_position = 0;
+            self.requestbar.vim.reset();
+        }
+    }
+
+    /// Writes the edited request back into the collection and saves it to disk.
+    pub fn save(&mut self) -> AppResult<()> {
+        let request = self.current_request();
+        if let Some(CollectionItem::Request(saved)) = self.collection.get_mut(&self.tab_path()) {
+            // Lines that do not parse would be lost, as only the parsed ones are stored.
+            let invalid = assertion::parse_lines(&self.requestbar.tests)
+                .into_iter()
+                .filter_map(|(line, assertion)| Some(format!("{}: {}", line, assertion.err()?)))
+                .chain(
+                    capture::parse_lines(&self.requestbar.captures)
+                        .into_iter()
+                        .filter_map(|(line, capture)| {
+                            Some(format!("{}: {}", line, capture.err()?))
+                        }),
+                )
+                .collect::<Vec<_>>();
+            if !invalid.is_empty() {
+                return Err(format!(
+                    "not saved, fix the tests and captures first: {}",
+                    invalid.join("; ")
+                )
+                .into());
+            }
+
+            *saved = request;
+        }
+
+        match &self.collection_path {
+            Some(path) => self.collection.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether a text input is capturing key presses.
+    pub fn is_inserting(&self) -> bool {
+        match self.selected {
+            Selected::Urlbar => matches!(self.urlbar.input_mode, InputMode::Insert),
+            Selected::Requestbar => matches!(self.requestbar.input_mode, InputMode::Insert),
+            Selected::Responsebar => {
+                self.responsebar.save_path.is_some()
+                    || self.responsebar.offset_input.is_some()
+                    || self.responsebar.header_input.is_some()
+                    || self.responsebar.filtering
+            }
+            _ => false,
+        }
+    }
+
+    /// The request as currently edited in the url and request bars.
+    pub fn current_request(&self) -> SavedRequest {
+        let saved = match self.collection.get(&self.tab_path()) {
+            Some(CollectionItem::Request(request)) => request.clone(),
+            _ => SavedRequest::default(),
         };
 
-        let mut req = reqwest::Request::new(method, url);
-        req.body_mut().replace(self.requestbar.body.clone().into());
+        SavedRequest {
+            method: self.urlbar.method.clone(),
+            url: self.urlbar.text.clone(),
+            body: self.requestbar.body.clone(),
+            assertions: assertion::parse_lines(&self.requestbar.tests)
+                .into_iter()
+                .filter_map(|(_, assertion)| assertion.ok())
+                .collect(),
+            captures: capture::parse_lines(&self.requestbar.captures)
+                .into_iter()
+                .filter_map(|(_, capture)| capture.ok())
+                .collect(),
+            scripts: Scripts {
+                pre_request: self.requestbar.pre_request.clone(),
+                post_response: self.requestbar.post_response.clone(),
+            },
+            disable_cookies: self.requestbar.disable_cookies,
+            ..saved
+        }
+    }
+
+    /// Sends the edited request in the background, see [`App::tick`].
+    pub fn request(&mut self) {
+        if self.pending.is_some() {
+            return;
+        }
+
+        for (line, capture) in capture::parse_lines(&self.requestbar.captures) {
+            if let Err(err) = capture {
+                self.console.push(format!("{}: {}", line, err));
+            }
+        }
 
-        let res = client.execute(req).await.unwrap();
-        let body = res.text().await.unwrap_or_default();
+        let client = self.http.clone();
+        let request = self.current_request();
+        let scripts = self.collection.folder_scripts(&self.tab_path());
+        let mut variables = self.variables();
+        let progress = Arc::new(Progress::default());
 
-        self.responsebar.body = body;
+        let task_progress = progress.clone();
+        let waker = self.waker.clone();
+        let handle = tokio::spawn(async move {
+            let mut logs = Vec::new();
+            let res = http::execute(
+                &client,
+                &request,
+                &scripts,
+                &mut variables,
+                &mut Vec::new(),
+                &mut logs,
+                &task_progress,
+            )
+            .await
+            .map_err(|err| err.to_string());
+            wake(waker);
+            (res, variables, logs)
+        });
+
+        self.responsebar.response = None;
+        self.responsebar.body = String::new();
+        self.pending = Some(PendingRequest {
+            progress,
+            started: Instant::now(),
+            handle,
+        });
     }
 
-    pub async fn handle_key_events(&mut self, key_event: KeyEvent) -> AppResult<()> {
-        // global key handlers
-        match key_event.code {
-            KeyCode::Char('c') | KeyCode::Char('C') => {
-                if key_event.modifiers == KeyModifiers::CONTROL {
-                    self.quit();
+    /// Stops the request being sent.
+    pub fn cancel_request(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.handle.abort();
+            self.responsebar.body = String::from("request cancelled");
+        }
+    }
+
+    fn finish_request(&mut self, (res, variables, logs): RequestOutcome) {
+        self.set_variables(variables);
+        self.console.extend(logs);
+        self.save_cookies();
+
+        self.requestbar.test_results = match &res {
+            Ok(Some(res)) => assertion::parse_lines(&self.requestbar.tests)
+                .into_iter()
+                .map(|(line, assertion)| match assertion {
+                    Ok(assertion) => assertion.evaluate(res),
+                    Err(err) => AssertionResult::invalid(&line, err),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        self.responsebar.response = None;
+        match res {
+            Ok(Some(res)) => self.responsebar.set_response(res),
+            Ok(None) => {
+                self.responsebar.body = String::from("request skipped by a pre-request script")
+            }
+            Err(err) => {
+                self.notify_error(err.clone());
+                self.responsebar.body = err;
+            }
+        }
+    }
+
+    /// Opens the runner for the folder or request selected in the sidebar.
+    pub fn open_runner(&mut self) {
+        let path = self
+            .sidebar
+            .selected()
+            .map(|item| item.inner().borrow().path.clone())
+            .unwrap_or_default();
+
+        let mut options = RunOptions::default();
+        let mut message = None;
+        if let Some(data) = self.collection.data_file(&path) {
+            let data = match &self.collection_path {
+                Some(collection) => collection.parent().unwrap_or(collection).join(data),
+                None => data.to_path_buf(),
+            };
+            match runner::load_data(&data) {
+                Ok(rows) => {
+                    options.iterations = rows.len().max(1);
+                    options.data = rows;
                 }
+                Err(err) => message = Some(err.to_string()),
             }
+        }
+
+        let runner = Runner::new(
+            self.http.clone(),
+            runner::collect(&self.collection, &path),
+            self.variables(),
+            options,
+        );
+        let mut runnerbar = RunnerBar::new(runner);
+        runnerbar.message = message;
+        self.runner = Some(runnerbar);
+    }
+
+    pub fn handle_runner_key_events(&mut self, key_event: KeyEvent) {
+        let Some(action) = self.keymap.lookup(&[Context::Runner], key_event, false) else {
+            return;
+        };
+        let Some(runnerbar) = &mut self.runner else {
+            return;
+        };
 
-            KeyCode::Char('b') | KeyCode::Char('B') => {
-                if key_event.modifiers == KeyModifiers::CONTROL {
-                    self.toggle_sidebar();
+        let idle = !runnerbar.runner.is_running();
+        match action {
+            Action::Quit => self.quit(),
+            Action::ToggleHelp => self.toggle_help(),
+            Action::Cancel => {
+                if let Some(pending) = runnerbar.pending.take() {
+                    pending.abort();
                 }
+                self.runner = None;
+                self.save_cookies();
+            }
+            Action::StartRun if idle => {
+                runnerbar.scroll = 0;
+                runnerbar.message = None;
+                runnerbar.runner.start();
             }
+            Action::ExportSummary if runnerbar.runner.is_finished() => {
+                let path = PathBuf::from("run-summary.json");
+                runnerbar.message = Some(match runnerbar.runner.summary().export(&path) {
+                    Ok(()) => format!("summary exported to {}", path.display()),
+                    Err(err) => err.to_string(),
+                });
+            }
+            Action::MoreIterations if idle => runnerbar.more_iterations(),
+            Action::FewerIterations if idle => runnerbar.less_iterations(),
+            Action::LongerDelay if idle => runnerbar.more_delay(),
+            Action::ShorterDelay if idle => runnerbar.less_delay(),
+            Action::ToggleStopOnFailure if idle => runnerbar.toggle_stop_on_failure(),
+            Action::Down => runnerbar.down(),
+            Action::Up => runnerbar.up(),
+            _ => {}
+        }
+    }
 
-            KeyCode::Esc | KeyCode::Char('q') => {
-                // if !self.urlbar.method_menu.is_open() {
-                //     self.quit();
-                // }
+    pub fn handle_client_key_events(&mut self, key_event: KeyEvent) {
+        let Some(clientbar) = &mut self.client_settings else {
+            return;
+        };
+
+        if let Some(input) = &mut clientbar.input {
+            match key_event.code {
+                KeyCode::Esc => clientbar.input = None,
+                KeyCode::Enter => clientbar.confirm(),
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                _ => {}
             }
+            return;
+        }
 
-            KeyCode::Tab | KeyCode::Char('.') | KeyCode::Char(']') => {
+        let Some(action) = self
+            .keymap
+            .lookup(&[Context::ClientSettings], key_event, false)
+        else {
+            return;
+        };
+        match action {
+            Action::Quit => self.quit(),
+            Action::ToggleHelp => self.toggle_help(),
+            Action::Cancel => {
+                let mut config = self.config.clone();
+                config.client = clientbar.settings.clone();
+                if config != self.config {
+                    let res = self
+                        .set_config(config)
+                        .and_then(|_| config::save_client(&self.config.client));
+                    if let Err(err) = res {
+                        self.notify_error(err.to_string());
+                    }
+                }
+                self.client_settings = None;
+            }
+            Action::Down => clientbar.down(),
+            Action::Up => clientbar.up(),
+            Action::Activate => clientbar.change(),
+            _ => {}
+        }
+    }
+
+    /// Opens the request selected in the sidebar in a tab, or toggles the selected folder.
+    pub fn open_sidebar_item(&mut self) {
+        if let Some(item) = self.sidebar.selected() {
+            if item.children().is_empty() {
+                let path = item.inner().borrow().path.clone();
+                match self
+                    .tabs
+                    .tabs
+                    .iter()
+                    .position(|tab| tab.borrow().path == path)
+                {
+                    Some(i) => {
+                        self.tabs.selected = i;
+                        self.selected = Selected::Tabs;
+                    }
+                    None => {
+                        self.tabs.add(item.inner().clone());
+                        self.tabs.selected = self.tabs.tabs.len() - 1;
+                        self.selected = Selected::Tabs;
+                    }
+                }
+                self.load_tab();
+            } else {
+                self.sidebar.tree.toggle();
+            }
+        }
+    }
+
+    /// Opens the request at the given collection path in a tab, reusing the tab it is open in.
+    pub fn open_request(&mut self, path: &[usize]) {
+        let Some(CollectionItem::Request(request)) = self.collection.get(path) else {
+            return;
+        };
+
+        match self
+            .tabs
+            .tabs
+            .iter()
+            .position(|tab| tab.borrow().path == path)
+        {
+            Some(i) => self.tabs.selected = i,
+            None => {
+                self.tabs.add(Item::with_path(&request.name, path.to_vec()));
+                self.tabs.selected = self.tabs.tabs.len() - 1;
+            }
+        }
+        self.selected = Selected::Tabs;
+        self.load_tab();
+    }
+
+    /// Copies text to the clipboard, telling in the console whether it worked.
+    pub fn yank(&mut self, what: &str, text: &str) {
+        match self.clipboard.copy(text) {
+            Ok(()) => self.notify(format!("copied {} to the clipboard", what)),
+            Err(err) => self.notify_error(format!("could not copy {}: {}", what, err)),
+        }
+    }
+
+    /// Lines of the body in view, inside the borders and below the filter prompt when it is shown.
+    fn body_height(&self) -> usize {
+        let prompt = (self.responsebar.filtering || !self.responsebar.filter.is_empty()) as u16;
+        self.areas.response.height.saturating_sub(2 + prompt) as usize
+    }
+
+    /// Writes the whole last response body to the path typed in the response bar.
+    pub fn save_response(&mut self) {
+        let (Some(path), Some(res)) = (
+            self.responsebar.save_path.take(),
+            &self.responsebar.response,
+        ) else {
+            return;
+        };
+
+        match res.raw.save(path.as_ref()) {
+            Ok(()) => {
+                let message = format!("saved {} to {}", download::format_size(res.raw.size), path);
+                self.notify(message)
+            }
+            Err(err) => self.notify_error(format!("cannot save to {}: {}", path, err)),
+        }
+    }
+
+    /// Whether certificates are not verified for the url in the url bar.
+    pub fn skips_verification(&self) -> bool {
+        http::parse_url(&substitute(&self.urlbar.text, &self.variables()))
+            .ok()
+            .and_then(|url| self.http.tls(&url).map(|tls| tls.skip_verify))
+            .unwrap_or(false)
+    }
+
+    pub fn open_cookies(&mut self) {
+        self.cookies = Some(CookieBar::new(self.http.cookies.list()));
+    }
+
+    pub fn handle_cookie_key_events(&mut self, key_event: KeyEvent) {
+        let Some(cookiebar) = &mut self.cookies else {
+            return;
+        };
+
+        if let Some(input) = &mut cookiebar.input {
+            match key_event.code {
+                KeyCode::Esc => cookiebar.input = None,
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => match cookiebar.parse_input() {
+                    Ok((domain, path, name, value)) => {
+                        // The edited cookie stays, with the input open, unless the new one is valid.
+                        if let Err(err) = self.http.cookies.set(&domain, &path, &name, &value) {
+                            cookiebar.message = Some(err.to_string());
+                            return;
+                        }
+                        if let Some(old) = cookiebar.editing.take() {
+                            let domain = domain.trim_start_matches('.');
+                            if !old.domain.eq_ignore_ascii_case(domain)
+                                || old.path != path
+                                || old.name != name
+                            {
+                                self.http.cookies.remove(&old.domain, &old.path, &old.name);
+                            }
+                        }
+                        cookiebar.message = None;
+                        cookiebar.input = None;
+                        cookiebar.refresh(self.http.cookies.list());
+                        self.save_cookies();
+                    }
+                    Err(err) => cookiebar.message = Some(err),
+                },
+                _ => {}
+            }
+            return;
+        }
+
+        let Some(action) = self.keymap.lookup(&[Context::Cookies], key_event, false) else {
+            return;
+        };
+        match action {
+            Action::Quit => self.quit(),
+            Action::ToggleHelp => self.toggle_help(),
+            Action::Cancel => self.cookies = None,
+            Action::Down => cookiebar.down(),
+            Action::Up => cookiebar.up(),
+            Action::AddCookie => cookiebar.add(),
+            Action::Activate => cookiebar.edit(),
+            Action::DeleteCookie => {
+                if let Some(cookie) = cookiebar.selected().cloned() {
+                    self.http
+                        .cookies
+                        .remove(&cookie.domain, &cookie.path, &cookie.name);
+                    cookiebar.refresh(self.http.cookies.list());
+                    self.save_cookies();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Opens the command palette over the actions, requests and environments.
+    pub fn open_palette(&mut self) {
+        let contexts = self.contexts();
+        let actions = Action::iter()
+            .filter(|action| {
+                !matches!(
+                    action,
+                    Action::Up
+                        | Action::Down
+                        | Action::Left
+                        | Action::Right
+                        | Action::First
+                        | Action::Last
+                        | Action::PageUp
+                        | Action::PageDown
+                        | Action::Activate
+                        | Action::Cancel
+                        | Action::OpenPalette
+                        | Action::StartRun
+                        | Action::ExportSummary
+                        | Action::MoreIterations
+                        | Action::FewerIterations
+                        | Action::LongerDelay
+                        | Action::ShorterDelay
+                        | Action::ToggleStopOnFailure
+                        | Action::AddCookie
+                        | Action::DeleteCookie
+                )
+            })
+            .map(|action| Entry {
+                label: action.label(),
+                keys: self.keymap.keys(&contexts, action).join(", "),
+                command: Command::Action(action),
+            });
+        let requests = self
+            .collection
+            .requests()
+            .into_iter()
+            .map(|(path, name)| Entry {
+                label: name,
+                keys: String::new(),
+                command: Command::Request(path),
+            });
+        let environments = self
+            .collection
+            .environments
+            .iter()
+            .enumerate()
+            .map(|(i, env)| Entry {
+                label: format!("Environment: {}", env.name),
+                keys: String::new(),
+                command: Command::Environment(i),
+            });
+
+        self.palette = Some(PaletteBar::new(
+            actions.chain(requests).chain(environments).collect(),
+        ));
+    }
+
+    pub fn handle_palette_key_events(&mut self, key_event: KeyEvent) -> AppResult<()> {
+        let Some(palettebar) = &mut self.palette else {
+            return Ok(());
+        };
+
+        // Keys that are not bound type the query.
+        let Some(action) = self.keymap.lookup(&[Context::Palette], key_event, false) else {
+            match key_event.code {
+                KeyCode::Backspace => palettebar.pop(),
+                KeyCode::Char(c)
+                    if !key_event
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    palettebar.push(c)
+                }
+                _ => {}
+            }
+            return Ok(());
+        };
+        match action {
+            Action::Quit => self.quit(),
+            Action::Cancel => self.palette = None,
+            Action::Up => palettebar.up(),
+            Action::Down => palettebar.down(),
+            Action::Activate => {
+                let command = palettebar.selected().map(|entry| entry.command.clone());
+                self.palette = None;
+                match command {
+                    Some(Command::Action(action)) => return self.perform(action),
+                    Some(Command::Request(path)) => self.open_request(&path),
+                    Some(Command::Environment(i)) => self.switch_environment(i),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn handle_help_key_events(&mut self, key_event: KeyEvent) {
+        let Some(action) = self.keymap.lookup(&[Context::Help], key_event, false) else {
+            return;
+        };
+        match action {
+            Action::Quit => self.quit(),
+            Action::Cancel => self.settings.show_help = false,
+            Action::Down => self.help_scroll = self.help_scroll.saturating_add(1),
+            Action::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+            Action::PageDown => self.help_scroll = self.help_scroll.saturating_add(10),
+            Action::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+            Action::First => self.help_scroll = 0,
+            // Drawing the help keeps the scroll within its lines.
+            Action::Last => self.help_scroll = u16::MAX,
+            _ => {}
+        }
+    }
+
+    /// Shows or hides the key bindings of the focused pane or popup.
+    pub fn toggle_help(&mut self) {
+        self.settings.show_help = !self.settings.show_help;
+        self.help_scroll = 0;
+    }
+
+    pub async fn handle_key_events(&mut self, key_event: KeyEvent) -> AppResult<()> {
+        if self.settings.show_help {
+            self.handle_help_key_events(key_event);
+            return Ok(());
+        }
+
+        if self.palette.is_some() {
+            return self.handle_palette_key_events(key_event);
+        }
+
+        if self.runner.is_some() {
+            self.handle_runner_key_events(key_event);
+            return Ok(());
+        }
+
+        if self.cookies.is_some() {
+            self.handle_cookie_key_events(key_event);
+            return Ok(());
+        }
+
+        if self.client_settings.is_some() {
+            self.handle_client_key_events(key_event);
+            return Ok(());
+        }
+
+        let inserting = self.is_inserting();
+        if !inserting && self.edit_vim(key_event) {
+            return Ok(());
+        }
+        if let Some(action) = self.keymap.lookup(&self.contexts(), key_event, inserting) {
+            return self.perform(action);
+        }
+        if !inserting {
+            return Ok(());
+        }
+
+        match self.selected {
+            Selected::Urlbar => self.edit_url(key_event),
+            Selected::Requestbar => self.requestbar.edit(key_event),
+            Selected::Responsebar => self.edit_response_prompt(key_event),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Contexts of the key bindings that apply, the most specific first.
+    pub fn contexts(&self) -> Vec<Context> {
+        if let Some(popup) = self.popup() {
+            return popup.chain();
+        }
+        let context = match self.selected {
+            Selected::Sidebar => Context::Sidebar,
+            Selected::Tabs => Context::Tabs,
+            Selected::MethodBar => Context::MethodBar,
+            Selected::Urlbar => Context::Urlbar,
+            Selected::RequestTab => Context::RequestTab,
+            Selected::Requestbar => Context::Requestbar,
+            Selected::Responsebar => match self.responsebar.response_menu {
+                ResponseMenu::Body => Context::ResponseBody,
+                ResponseMenu::Tree => Context::ResponseTree,
+                ResponseMenu::Hex => Context::ResponseHex,
+                ResponseMenu::Timeline => Context::Responsebar,
+            },
+        };
+        context.chain()
+    }
+
+    /// Context of the popup that takes the keys, apart from the help.
+    pub fn popup(&self) -> Option<Context> {
+        if self.palette.is_some() {
+            Some(Context::Palette)
+        } else if self.runner.is_some() {
+            Some(Context::Runner)
+        } else if self.cookies.is_some() {
+            Some(Context::Cookies)
+        } else if self.client_settings.is_some() {
+            Some(Context::ClientSettings)
+        } else {
+            None
+        }
+    }
+
+    /// Does what a key binding asks, in the focused pane.
+    pub fn perform(&mut self, action: Action) -> AppResult<()> {
+        match action {
+            Action::Quit => self.quit(),
+            Action::Save => {
+                self.save()?;
+                self.notify("saved");
+            }
+            Action::ToggleSidebar => self.toggle_sidebar(),
+            Action::ToggleConsole => self.settings.show_console = !self.settings.show_console,
+            Action::OpenCookies => self.open_cookies(),
+            Action::OpenClientSettings => {
+                self.client_settings = Some(ClientBar::new(self.config.client.clone()))
+            }
+            Action::OpenRunner => self.open_runner(),
+            Action::OpenEditor => self.open_editor(),
+            Action::OpenPalette => self.open_palette(),
+            Action::WidenSidebar => self.resize_sidebar(2),
+            Action::NarrowSidebar => self.resize_sidebar(-2),
+            Action::GrowRequest => self.resize_split(5),
+            Action::ShrinkRequest => self.resize_split(-5),
+            Action::CycleOrientation => {
+                self.settings.orientation = self.settings.orientation.next();
+                self.notify(format!("layout: {}", self.settings.orientation));
+            }
+            Action::ToggleMaximize => self.settings.maximized = !self.settings.maximized,
+            Action::ToggleHelp => self.toggle_help(),
+            Action::NextPane => {
                 self.selected = match self.selected {
                     Selected::Sidebar => Selected::Tabs,
                     Selected::Tabs => Selected::MethodBar,
@@ -207,10 +1281,9 @@ impl App {
                     Selected::RequestTab => Selected::Requestbar,
                     Selected::Requestbar => Selected::Responsebar,
                     Selected::Responsebar => Selected::Sidebar,
-                };
+                }
             }
-
-            KeyCode::Char(',') | KeyCode::Char('[') => {
+            Action::PreviousPane => {
                 self.selected = match self.selected {
                     Selected::Sidebar => Selected::Responsebar,
                     Selected::Tabs => Selected::Sidebar,
@@ -221,80 +1294,161 @@ impl App {
                     Selected::Responsebar => Selected::Requestbar,
                 }
             }
-
-            // Other handlers you could add here.
-            _ => {}
-        }
-
-        match self.selected {
-            Selected::Sidebar => match key_event.code {
-                KeyCode::Char(' ') | KeyCode::Char('o') | KeyCode::Enter => {
-                    if let Some(item) = self.sidebar.selected() {
-                        if item.children().is_empty() {
-                            let item_name = item.inner().to_string();
-                            match self
-                                .tabs
-                                .tabs
-                                .iter()
-                                .enumerate()
-                                .find(|(_, item)| item.borrow().name == item_name)
-                                .map(|(i, _)| i)
-                            {
-                                Some(i) => {
-                                    self.tabs.selected = i;
-                                    self.selected = Selected::Tabs;
-                                }
-                                None => {
-                                    self.tabs.add(item.inner().clone());
-                                    self.tabs.selected = self.tabs.tabs.len() - 1;
-                                    self.selected = Selected::Tabs;
-                                }
-                            }
-                        } else {
-                            self.sidebar.tree.toggle();
-                        }
+            Action::Insert => match self.selected {
+                Selected::Urlbar => {
+                    let urlbar = &mut self.urlbar;
+                    urlbar.vim.snapshot(&urlbar.text, urlbar.cursor_position);
+                    urlbar.input_mode = InputMode::Insert;
+                }
+                Selected::Requestbar => {
+                    if let Some(text) = self.requestbar.text() {
+                        let (text, cursor) = (text.to_string(), self.requestbar.cursor_position);
+                        self.requestbar.vim.snapshot(&text, cursor);
+                        self.requestbar.input_mode = InputMode::Insert;
                     }
                 }
-
-                KeyCode::Left => self.sidebar.tree.left(),
-                KeyCode::Right => self.sidebar.tree.right(),
-                KeyCode::Down => self.sidebar.tree.down(),
-                KeyCode::Up => self.sidebar.tree.up(),
-                KeyCode::Home => self.sidebar.tree.first(),
-                KeyCode::End => self.sidebar.tree.last(),
-
                 _ => {}
             },
-            Selected::Tabs => match key_event.code {
-                KeyCode::Left => {
-                    self.tabs.left();
+            Action::Redo => match (&self.selected, self.is_inserting()) {
+                (Selected::Urlbar, false) => self.urlbar.redo(),
+                (Selected::Requestbar, false) => self.requestbar.redo(),
+                _ => {}
+            },
+            Action::Send => self.request(),
+            Action::ToggleCookies => {
+                self.requestbar.disable_cookies = !self.requestbar.disable_cookies
+            }
+            Action::PreviousTab => self.responsebar.left(),
+            Action::NextTab => self.responsebar.right(),
+            Action::SaveResponse => {
+                if self.responsebar.response.is_some() {
+                    self.responsebar.save_path = Some(String::from("response.body"))
                 }
-                KeyCode::Right => {
-                    self.tabs.right();
+            }
+            Action::CancelRequest => self.cancel_request(),
+            Action::CycleFormat => self.responsebar.cycle_format(),
+            Action::Filter => self.responsebar.filtering = true,
+            Action::GotoOffset => self.responsebar.offset_input = Some(String::new()),
+            Action::CopyValue => {
+                if let Some(text) = self
+                    .responsebar
+                    .json
+                    .as_ref()
+                    .and_then(|json| json.selected_text())
+                {
+                    self.yank("value", &text);
                 }
-                KeyCode::Down => {
-                    self.tabs.right();
+            }
+            Action::CopyPath => {
+                if let Some((_, path)) = self
+                    .responsebar
+                    .json
+                    .as_ref()
+                    .and_then(|json| json.selected())
+                {
+                    self.yank("JSONPath", &path);
                 }
-                KeyCode::Up => {
-                    self.tabs.left();
+            }
+            Action::CopyUrl => {
+                let url = substitute(&self.urlbar.text, &self.variables());
+                self.yank("URL", &url);
+            }
+            Action::CopyCurl => {
+                let curl = http::curl(&self.current_request(), &self.variables());
+                self.yank("curl command", &curl);
+            }
+            Action::CopyBody => match &self.responsebar.response {
+                Some(res) if res.binary => self.notify_error("the body is binary, save it instead"),
+                Some(res) => match res.text().map(Cow::into_owned) {
+                    Ok(body) => self.yank("body", &body),
+                    Err(err) => self.notify_error(format!("could not copy the body: {}", err)),
+                },
+                None => {}
+            },
+            Action::SelectLines => {
+                if self
+                    .responsebar
+                    .response
+                    .as_ref()
+                    .is_some_and(|res| !res.binary)
+                {
+                    self.responsebar.toggle_selection();
                 }
-                KeyCode::Home => {
-                    self.tabs.first();
+            }
+            Action::CopyRegion => {
+                if self.responsebar.response.is_some() {
+                    match self.responsebar.region() {
+                        Some(region) => {
+                            self.responsebar.selection = None;
+                            self.yank("lines", &region);
+                        }
+                        None => self.notify_error("no lines selected, v starts a selection"),
+                    }
                 }
-                KeyCode::End => {
-                    self.tabs.last();
+            }
+            Action::CopyHeader => {
+                if self.responsebar.response.is_some() {
+                    self.responsebar.header_input = Some(String::new())
                 }
-                KeyCode::Enter => {}
+            }
+            Action::Up
+            | Action::Down
+            | Action::Left
+            | Action::Right
+            | Action::First
+            | Action::Last
+            | Action::PageUp
+            | Action::PageDown
+            | Action::Activate
+            | Action::Cancel => self.navigate(action),
+            // Bound in the popups, which handle them.
+            Action::StartRun
+            | Action::ExportSummary
+            | Action::MoreIterations
+            | Action::FewerIterations
+            | Action::LongerDelay
+            | Action::ShorterDelay
+            | Action::ToggleStopOnFailure
+            | Action::AddCookie
+            | Action::DeleteCookie => {}
+        }
+        Ok(())
+    }
+
+    /// Moves around the focused pane.
+    fn navigate(&mut self, action: Action) {
+        match self.selected {
+            Selected::Sidebar => match action {
+                Action::Activate => self.open_sidebar_item(),
+                Action::Left => self.sidebar.tree.left(),
+                Action::Right => self.sidebar.tree.right(),
+                Action::Down => self.sidebar.tree.down(),
+                Action::Up => self.sidebar.tree.up(),
+                Action::First => self.sidebar.tree.first(),
+                Action::Last => self.sidebar.tree.last(),
                 _ => {}
             },
+            Selected::Tabs => {
+                let selected = self.tabs.selected;
+                match action {
+                    Action::Left | Action::Up => self.tabs.left(),
+                    Action::Right | Action::Down => self.tabs.right(),
+                    Action::First => self.tabs.first(),
+                    Action::Last => self.tabs.last(),
+                    _ => {}
+                }
+                if self.tabs.selected != selected {
+                    self.load_tab();
+                }
+            }
             Selected::MethodBar => {
-                match key_event.code {
-                    KeyCode::Char('h') | KeyCode::Left => self.urlbar.method_menu.left(),
-                    KeyCode::Char('l') | KeyCode::Right => self.urlbar.method_menu.right(),
-                    KeyCode::Char('j') | KeyCode::Down => self.urlbar.method_menu.down(),
-                    KeyCode::Char('k') | KeyCode::Up => self.urlbar.method_menu.up(),
-                    KeyCode::Esc => self.urlbar.method_menu.reset(),
-                    KeyCode::Enter => self.urlbar.method_menu.select(),
+                match action {
+                    Action::Left => self.urlbar.method_menu.left(),
+                    Action::Right => self.urlbar.method_menu.right(),
+                    Action::Down => self.urlbar.method_menu.down(),
+                    Action::Up => self.urlbar.method_menu.up(),
+                    Action::Cancel => self.urlbar.method_menu.reset(),
+                    Action::Activate => self.urlbar.method_menu.select(),
                     _ => {}
                 };
 
@@ -308,66 +1462,434 @@ impl App {
                     }
                 }
             }
-            Selected::Urlbar => match self.urlbar.input_mode {
-                InputMode::Normal => match key_event.code {
-                    KeyCode::Enter | KeyCode::Char('i') => self.urlbar.input_mode = InputMode::Insert,
-                    KeyCode::Char('o') => {
-                        self.request().await;
-                    }
+            Selected::RequestTab => match action {
+                Action::Left | Action::Down => self.requestbar.left(),
+                Action::Right | Action::Up => self.requestbar.right(),
+                _ => {}
+            },
+            Selected::Responsebar => match self.responsebar.response_menu {
+                ResponseMenu::Body if self.responsebar.selection.is_some() => {
+                    let height = self.body_height();
+                    let lines = match action {
+                        Action::Down => 1,
+                        Action::Up => -1,
+                        Action::PageDown => 16,
+                        Action::PageUp => -16,
+                        Action::First => isize::MIN,
+                        Action::Last => isize::MAX,
+                        _ => 0,
+                    };
+                    self.responsebar.extend_selection(lines, height);
+                }
+                ResponseMenu::Body => match action {
+                    Action::Down => self.responsebar.scroll_body(1),
+                    Action::Up => self.responsebar.scroll_body(-1),
+                    Action::PageDown => self.responsebar.scroll_body(16),
+                    Action::PageUp => self.responsebar.scroll_body(-16),
+                    Action::First => self.responsebar.body_scroll = 0,
+                    Action::Last => self.responsebar.scroll_body(isize::MAX),
                     _ => {}
                 },
-                InputMode::Insert => {
-                    match key_event.code {
-                        // KeyCode::Esc => self.urlbar.input_mode = InputMode::Normal,
-                        KeyCode::Enter => self.urlbar.input_mode = InputMode::Normal,
-                        KeyCode::Char(c) => {
-                            self.urlbar.text.insert(self.urlbar.cursor_position, c);
-                            self.urlbar.cursor_position += 1;
-                        }
-                        KeyCode::Backspace => {
-                            if self.urlbar.cursor_position > 0 {
-                                self.urlbar.cursor_position -= 1;
-                                self.urlbar.text.remove(self.urlbar.cursor_position);
-                            }
-                        }
-                        KeyCode::Delete => {
-                            if self.urlbar.cursor_position < self.urlbar.text.len() {
-                                self.urlbar.text.remove(self.urlbar.cursor_position);
-                            }
-                        }
-                        KeyCode::Left => {
-                            if self.urlbar.cursor_position > 0 {
-                                self.urlbar.cursor_position -= 1;
-                            }
-                        }
-                        KeyCode::Right => {
-                            if self.urlbar.cursor_position < self.urlbar.text.len() {
-                                self.urlbar.cursor_position += 1;
-                            }
-                        }
-                        KeyCode::Home => {
-                            self.urlbar.cursor_position = 0;
-                        }
-                        KeyCode::End => {
-                            self.urlbar.cursor_position = self.urlbar.text.len();
+                ResponseMenu::Tree => {
+                    if let Some(json) = &mut self.responsebar.json {
+                        match action {
+                            Action::Down => json.tree.down(),
+                            Action::Up => json.tree.up(),
+                            Action::Left => json.tree.left(),
+                            Action::Right => json.tree.right(),
+                            Action::First => json.tree.first(),
+                            Action::Last => json.tree.last(),
+                            Action::Activate => json.tree.toggle(),
+                            _ => {}
                         }
-                        _ => {}
                     }
                 }
+                ResponseMenu::Hex => match action {
+                    Action::Down => self.responsebar.scroll_down(1),
+                    Action::Up => self.responsebar.scroll_up(1),
+                    Action::PageDown => self.responsebar.scroll_down(16),
+                    Action::PageUp => self.responsebar.scroll_up(16),
+                    Action::First => self.responsebar.hex_scroll = 0,
+                    Action::Last => {
+                        self.responsebar.hex_scroll = self.responsebar.hex_rows().saturating_sub(1)
+                    }
+                    _ => {}
+                },
+                ResponseMenu::Timeline => {}
             },
-            Selected::RequestTab => {
-                match key_event.code {
-                    KeyCode::Char('h') | KeyCode::Left => self.requestbar.left(),
-                    KeyCode::Char('l') | KeyCode::Right => self.requestbar.right(),
-                    KeyCode::Char('j') | KeyCode::Down => self.requestbar.left(),
-                    KeyCode::Char('k') | KeyCode::Up => self.requestbar.right(),
+            Selected::Urlbar | Selected::Requestbar => {}
+        }
+    }
 
-                    _ => {}
+    /// Asks the main loop to open the focused text in the external editor.
+    pub fn open_editor(&mut self) {
+        let edit = match (&self.selected, &self.requestbar.request_menu) {
+            (Selected::Urlbar, _) => Edit {
+                target: Target::Url,
+                text: self.urlbar.text.clone(),
+                extension: "txt",
+            },
+            (Selected::RequestTab | Selected::Requestbar, RequestMenu::Headers) => Edit {
+                target: Target::Headers,
+                text: self
+                    .current_request()
+                    .headers
+                    .iter()
+                    .map(|header| format!("{}\n", header))
+                    .collect(),
+                extension: "txt",
+            },
+            (Selected::RequestTab | Selected::Requestbar, menu) => {
+                let Some(text) = self.requestbar.text() else {
+                    return;
+                };
+                let extension = match menu {
+                    RequestMenu::Body => editor::extension(text),
+                    RequestMenu::PreRequest | RequestMenu::PostResponse => "rhai",
+                    _ => "txt",
+                };
+                Edit {
+                    target: Target::Request,
+                    text: text.to_string(),
+                    extension,
+                }
+            }
+            _ => return,
+        };
+
+        self.urlbar.input_mode = InputMode::Normal;
+        self.requestbar.input_mode = InputMode::Normal;
+        self.editing = Some(edit);
+    }
+
+    /// Reads back the text edited in the external editor.
+    pub fn finish_edit(&mut self, edit: Edit, result: AppResult<String>) {
+        let text = match result {
+            Ok(text) if text == edit.text => return,
+            Ok(text) => text,
+            Err(err) => {
+                self.notify_error(format!("editor: {}", err));
+                return;
+            }
+        };
+
+        match edit.target {
+            Target::Url => {
+                let urlbar = &mut self.urlbar;
+                urlbar.vim.snapshot(&urlbar.text, urlbar.cursor_position);
+                urlbar.text = text.trim().replace('\n', "");
+                urlbar.cursor_position = 0;
+            }
+            Target::Request => {
+                let requestbar = &mut self.requestbar;
+                requestbar
+                    .vim
+                    .snapshot(&edit.text, requestbar.cursor_position);
+                let field = match requestbar.request_menu {
+                    RequestMenu::Body => &mut requestbar.body,
+                    RequestMenu::Tests => &mut requestbar.tests,
+                    RequestMenu::Captures => &mut requestbar.captures,
+                    RequestMenu::PreRequest => &mut requestbar.pre_request,
+                    RequestMenu::PostResponse => &mut requestbar.post_response,
+                    _ => return,
                 };
+                *field = text;
+                requestbar.cursor_position = 0;
+                requestbar.scroll = 0;
+            }
+            Target::Headers => {
+                let mut headers = Vec::new();
+                for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                    match line.parse::<Header>() {
+                        Ok(header) => headers.push(header),
+                        Err(err) => self.console.push(err),
+                    }
+                }
+                if let Some(CollectionItem::Request(saved)) =
+                    self.collection.get_mut(&self.tab_path())
+                {
+                    let message = format!("{} header(s) set", headers.len());
+                    saved.headers = headers;
+                    self.notify(message);
+                } else {
+                    self.notify_error("headers are only kept on saved requests");
+                }
             }
-            Selected::Requestbar => {}
-            Selected::Responsebar => {}
         }
-        Ok(())
+    }
+
+    /// Passes a key to vim editing in the url bar or the request text, returning whether it was
+    /// a vim command.
+    fn edit_vim(&mut self, key_event: KeyEvent) -> bool {
+        match self.selected {
+            Selected::Urlbar => self.urlbar.vim(key_event),
+            Selected::Requestbar => self.requestbar.vim(key_event),
+            _ => false,
+        }
+    }
+
+    /// Types into the url bar.
+    fn edit_url(&mut self, key_event: KeyEvent) {
+        let (text, cursor) = (&mut self.urlbar.text, &mut self.urlbar.cursor_position);
+
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter => self.urlbar.input_mode = InputMode::Normal,
+            KeyCode::Char(c) => {
+                text.insert(*cursor, c);
+                *cursor += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                if let Some(c) = text[..*cursor].chars().next_back() {
+                    *cursor -= c.len_utf8();
+                    text.remove(*cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if *cursor < text.len() {
+                    text.remove(*cursor);
+                }
+            }
+            KeyCode::Left => {
+                if let Some(c) = text[..*cursor].chars().next_back() {
+                    *cursor -= c.len_utf8();
+                }
+            }
+            KeyCode::Right => {
+                if let Some(c) = text[*cursor..].chars().next() {
+                    *cursor += c.len_utf8();
+                }
+            }
+            KeyCode::Home => *cursor = 0,
+            KeyCode::End => *cursor = text.len(),
+            _ => {}
+        }
+    }
+
+    /// Inserts pasted text where text is being typed.
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Some(palettebar) = &mut self.palette {
+            text.lines()
+                .flat_map(str::chars)
+                .for_each(|c| palettebar.push(c));
+            return;
+        }
+        if !self.is_inserting() {
+            return;
+        }
+
+        // Only the request text takes more than one line.
+        let line = text.replace(['\r', '\n'], "");
+        match self.selected {
+            Selected::Urlbar => {
+                let urlbar = &mut self.urlbar;
+                urlbar.text.insert_str(urlbar.cursor_position, &line);
+                urlbar.cursor_position += line.len();
+            }
+            Selected::Requestbar => self.requestbar.insert(&text.replace("\r\n", "\n")),
+            Selected::Responsebar => {
+                let responsebar = &mut self.responsebar;
+                if let Some(input) = responsebar
+                    .save_path
+                    .as_mut()
+                    .or(responsebar.offset_input.as_mut())
+                    .or(responsebar.header_input.as_mut())
+                {
+                    input.push_str(&line);
+                } else if responsebar.filtering {
+                    let filter = format!("{}{}", responsebar.filter, line);
+                    responsebar.recent_index = None;
+                    responsebar.set_filter(filter);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Types into the save, offset or filter prompt of the response bar.
+    fn edit_response_prompt(&mut self, key_event: KeyEvent) {
+        if let Some(path) = &mut self.responsebar.save_path {
+            match key_event.code {
+                KeyCode::Esc => self.responsebar.save_path = None,
+                KeyCode::Char(c) => path.push(c),
+                KeyCode::Backspace => {
+                    path.pop();
+                }
+                KeyCode::Enter => self.save_response(),
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(offset) = &mut self.responsebar.offset_input {
+            match key_event.code {
+                KeyCode::Esc => self.responsebar.offset_input = None,
+                KeyCode::Char(c) => offset.push(c),
+                KeyCode::Backspace => {
+                    offset.pop();
+                }
+                KeyCode::Enter => {
+                    if let Err(err) = self.responsebar.jump() {
+                        self.console.push(err);
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(name) = &mut self.responsebar.header_input {
+            match key_event.code {
+                KeyCode::Esc => self.responsebar.header_input = None,
+                KeyCode::Char(c) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter => {
+                    let name = self.responsebar.header_input.take().unwrap_or_default();
+                    match self.responsebar.header(&name).map(str::to_string) {
+                        Some(value) => self.yank(&format!("{} header", name.trim()), &value),
+                        None => self.notify_error(format!("no {} header in the response", name)),
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if self.responsebar.filtering {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.responsebar.filtering = false;
+                    self.responsebar.set_filter(String::new());
+                }
+                KeyCode::Enter => {
+                    self.responsebar.remember_filter();
+                    if let Some(CollectionItem::Request(saved)) =
+                        self.collection.get_mut(&self.tab_path())
+                    {
+                        saved.filters = self.responsebar.recent_filters.clone();
+                    }
+                }
+                KeyCode::Up => self.responsebar.recall(true),
+                KeyCode::Down => self.responsebar.recall(false),
+                KeyCode::Char(c) => self.responsebar.filter_push(c),
+                KeyCode::Backspace => self.responsebar.filter_pop(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Handles the mouse events and updates the state of [`App`].
+    pub fn handle_mouse_events(&mut self, mouse_event: MouseEvent) {
+        if self.runner.is_some()
+            || self.cookies.is_some()
+            || self.client_settings.is_some()
+            || self.palette.is_some()
+            || self.settings.show_help
+        {
+            return;
+        }
+
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.areas.on_split(column, row) => {
+                self.dragging = true
+            }
+            MouseEventKind::Down(MouseButton::Left) => self.click(column, row),
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging => {
+                self.settings.split = self.areas.split_at(column, row)
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragging = false,
+            MouseEventKind::ScrollDown => self.scroll(column, row, 3),
+            MouseEventKind::ScrollUp => self.scroll(column, row, -3),
+            _ => {}
+        }
+    }
+
+    /// Focuses the pane under the position and activates the tab, item or menu clicked in it.
+    fn click(&mut self, column: u16, row: u16) {
+        let areas = self.areas;
+        let selected = if mouse::contains(areas.sidebar, column, row) {
+            if let Some(index) = row.checked_sub(areas.sidebar.y + 1) {
+                self.selected = Selected::Sidebar;
+                if self.sidebar.tree.select_row(index as usize) {
+                    self.open_sidebar_item();
+                }
+                return;
+            }
+            Selected::Sidebar
+        } else if mouse::contains(areas.tabs, column, row) {
+            let tab = mouse::tab_at(self.tabs.tabs.iter(), areas.tabs.x + 1, column);
+            if let (Some(i), true) = (tab, row == areas.tabs.y + 1) {
+                if i != self.tabs.selected {
+                    self.tabs.selected = i;
+                    self.load_tab();
+                }
+            }
+            Selected::Tabs
+        } else if mouse::contains(areas.method, column, row) {
+            self.urlbar.method_menu.activate();
+            Selected::MethodBar
+        } else if mouse::contains(areas.url, column, row) {
+            Selected::Urlbar
+        } else if mouse::contains(areas.request_tabs, column, row) {
+            let tab = mouse::tab_at(RequestMenu::iter(), areas.request_tabs.x, column);
+            if let (Some(menu), true) = (tab, row == areas.request_tabs.y) {
+                self.requestbar
+                    .select(RequestMenu::iter().nth(menu).unwrap_or_default());
+            }
+            Selected::RequestTab
+        } else if mouse::contains(areas.request, column, row) {
+            Selected::Requestbar
+        } else if mouse::contains(areas.response_tabs, column, row) {
+            let tab = mouse::tab_at(ResponseMenu::iter(), areas.response_tabs.x, column);
+            if let (Some(menu), true) = (tab, row == areas.response_tabs.y) {
+                self.responsebar.response_menu = ResponseMenu::iter().nth(menu).unwrap_or_default();
+            }
+            Selected::Responsebar
+        } else if mouse::contains(areas.response, column, row) {
+            Selected::Responsebar
+        } else {
+            return;
+        };
+
+        if selected != self.selected {
+            self.urlbar.input_mode = InputMode::Normal;
+            self.requestbar.input_mode = InputMode::Normal;
+            self.selected = selected;
+        }
+    }
+
+    /// Scrolls the pane under the position by `lines`, downwards when positive.
+    fn scroll(&mut self, column: u16, row: u16, lines: isize) {
+        if mouse::contains(self.areas.request, column, row) {
+            self.requestbar.scroll_by(lines);
+        } else if mouse::contains(self.areas.response, column, row) {
+            match self.responsebar.response_menu {
+                ResponseMenu::Body => self.responsebar.scroll_body(lines),
+                ResponseMenu::Tree => {
+                    if let Some(json) = &mut self.responsebar.json {
+                        for _ in 0..lines.unsigned_abs() {
+                            match lines > 0 {
+                                true => json.tree.down(),
+                                false => json.tree.up(),
+                            }
+                        }
+                    }
+                }
+                ResponseMenu::Hex => match lines > 0 {
+                    true => self.responsebar.scroll_down(lines.unsigned_abs()),
+                    false => self.responsebar.scroll_up(lines.unsigned_abs()),
+                },
+                ResponseMenu::Timeline => {}
+            }
+        }
+    }
+}
+
+/// Tells the main loop that a request sent in the background finished.
+fn wake(waker: Option<mpsc::Sender<Event>>) {
+    if let Some(waker) = waker {
+        let _ = waker.send(Event::Wake);
     }
 }
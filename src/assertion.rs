@@ -0,0 +1,517 @@
+use std::{fmt, str::FromStr};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{http::Response, jsonpath};
+
+/// A check evaluated against the response after every send.
+///
+/// In the tests tab, assertions are written one per line:
+///
+/// ```text
+/// status == 200
+/// status in 200..299
+/// header Content-Type exists
+/// header Content-Type == application/json
+/// header Content-Type ~ json$
+/// jsonpath $.id == 5
+/// jsonpath $.name contains kanye
+/// jsonpath $.name ~ ^k
+/// schema {"type": "object", "required": ["id"]}
+/// time < 500
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    StatusEquals { status: u16 },
+    StatusIn { min: u16, max: u16 },
+    HeaderExists { name: String },
+    HeaderEquals { name: String, value: String },
+    HeaderMatches { name: String, pattern: String },
+    JsonPathEquals { path: String, value: Value },
+    JsonPathContains { path: String, value: String },
+    JsonPathMatches { path: String, pattern: String },
+    Schema { schema: Value },
+    ResponseTime { max_ms: u64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl AssertionResult {
+    /// A failed result for a line that could not be parsed.
+    pub fn invalid(line: &str, message: String) -> Self {
+        Self {
+            assertion: line.to_string(),
+            passed: false,
+            message: Some(message),
+        }
+    }
+}
+
+impl Assertion {
+    pub fn evaluate(&self, response: &Response) -> AssertionResult {
+        let outcome = self.check(response);
+
+        AssertionResult {
+            assertion: self.to_string(),
+            passed: outcome.is_ok(),
+            message: outcome.err(),
+        }
+    }
+
+    fn check(&self, response: &Response) -> Result<(), String> {
+        match self {
+            Assertion::StatusEquals { status } => expect(
+                response.status == *status,
+                format!("expected status {}, got {}", status, response.status),
+            ),
+            Assertion::StatusIn { min, max } => expect(
+                (*min..=*max).contains(&response.status),
+                format!(
+                    "expected status in {}..{}, got {}",
+                    min, max, response.status
+                ),
+            ),
+            Assertion::HeaderExists { name } => header(response, name).map(|_| ()),
+            Assertion::HeaderEquals { name, value } => {
+                let actual = header(response, name)?;
+                expect(
+                    actual == value,
+                    format!("expected header {} to be {}, got {}", name, value, actual),
+                )
+            }
+            Assertion::HeaderMatches { name, pattern } => {
+                let actual = header(response, name)?;
+                expect(
+                    regex(pattern)?.is_match(actual),
+                    format!(
+                        "expected header {} to match {}, got {}",
+                        name, pattern, actual
+                    ),
+                )
+            }
+            Assertion::JsonPathEquals { path, value } => {
+                let actual = first(response, path)?;
+                expect(
+                    actual == *value || jsonpath::text(&actual) == jsonpath::text(value),
+                    format!("expected {} to be {}, got {}", path, value, actual),
+                )
+            }
+            Assertion::JsonPathContains { path, value } => {
+                let actual = jsonpath::text(&first(response, path)?);
+                expect(
+                    actual.contains(value.as_str()),
+                    format!("expected {} to contain {}, got {}", path, value, actual),
+                )
+            }
+            Assertion::JsonPathMatches { path, pattern } => {
+                let actual = jsonpath::text(&first(response, path)?);
+                expect(
+                    regex(pattern)?.is_match(&actual),
+                    format!("expected {} to match {}, got {}", path, pattern, actual),
+                )
+            }
+            Assertion::Schema { schema } => {
+                let body: Value = serde_json::from_str(&response.text()?)
+                    .map_err(|err| format!("body is not JSON: {}", err))?;
+                let schema = jsonschema::JSONSchema::compile(schema)
+                    .map_err(|err| format!("invalid schema: {}", err))?;
+                schema.validate(&body).map_err(|errors| {
+                    errors
+                        .map(|err| match err.instance_path.to_string() {
+                            path if path.is_empty() => err.to_string(),
+                            path => format!("{} at {}", err, path),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+            }
+            Assertion::ResponseTime { max_ms } => {
+                let elapsed = response.elapsed.as_millis();
+                expect(
+                    elapsed < *max_ms as u128,
+                    format!("expected response under {}ms, took {}ms", max_ms, elapsed),
+                )
+            }
+        }
+    }
+}
+
+fn expect(condition: bool, message: String) -> Result<(), String> {
+    match condition {
+        true => Ok(()),
+        false => Err(message),
+    }
+}
+
+fn header<'a>(response: &'a Response, name: &str) -> Result<&'a str, String> {
+    response
+        .headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+        .ok_or_else(|| format!("header {} is missing", name))
+}
+
+fn first(response: &Response, path: &str) -> Result<Value, String> {
+    jsonpath::select_str(&response.text()?, path)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("nothing found at {}", path))
+}
+
+fn regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|err| format!("invalid regex {}: {}", pattern, err))
+}
+
+/// Splits off the first whitespace separated word.
+fn word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], text[i..].trim()),
+        None => (text, ""),
+    }
+}
+
+fn number<T: FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("expected a number, got {}", text))
+}
+
+impl FromStr for Assertion {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (subject, rest) = word(line);
+
+        match subject {
+            "status" => match word(rest) {
+                ("==", value) => Ok(Assertion::StatusEquals {
+                    status: number(value)?,
+                }),
+                ("in", range) => {
+                    let (min, max) = range
+                        .split_once("..")
+                        .ok_or_else(|| format!("expected a range like 200..299, got {}", range))?;
+                    Ok(Assertion::StatusIn {
+                        min: number(min)?,
+                        max: number(max)?,
+                    })
+                }
+                (op, _) => Err(format!("unknown status operator: {}", op)),
+            },
+            "header" | "jsonpath" => {
+                let (target, rest) = word(rest);
+                if target.is_empty() {
+                    return Err(format!("missing {} name", subject));
+                }
+                if subject == "jsonpath" {
+                    jsonpath::check(target)?;
+                }
+                let target = target.to_string();
+
+                match (subject, word(rest)) {
+                    ("header", ("exists", "")) => Ok(Assertion::HeaderExists { name: target }),
+                    ("header", ("==", value)) => Ok(Assertion::HeaderEquals {
+                        name: target,
+                        value: value.to_string(),
+                    }),
+                    ("header", ("~", pattern)) => {
+                        regex(pattern)?;
+                        Ok(Assertion::HeaderMatches {
+                            name: target,
+                            pattern: pattern.to_string(),
+                        })
+                    }
+                    (_, ("==", value)) => Ok(Assertion::JsonPathEquals {
+                        path: target,
+                        value: serde_json::from_str(value)
+                            .unwrap_or_else(|_| Value::String(value.to_string())),
+                    }),
+                    (_, ("contains", value)) => Ok(Assertion::JsonPathContains {
+                        path: target,
+                        value: value.to_string(),
+                    }),
+                    (_, ("~", pattern)) => {
+                        regex(pattern)?;
+                        Ok(Assertion::JsonPathMatches {
+                            path: target,
+                            pattern: pattern.to_string(),
+                        })
+                    }
+                    (_, (op, _)) => Err(format!("unknown {} operator: {}", subject, op)),
+                }
+            }
+            "schema" => Ok(Assertion::Schema {
+                schema: serde_json::from_str(rest)
+                    .map_err(|err| format!("invalid schema: {}", err))?,
+            }),
+            "time" => match word(rest) {
+                ("<", value) => Ok(Assertion::ResponseTime {
+                    max_ms: number(value.trim_end_matches("ms"))?,
+                }),
+                (op, _) => Err(format!("unknown time operator: {}", op)),
+            },
+            _ => Err(format!("unknown assertion: {}", line.trim())),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Assertion::StatusEquals { status } => write!(f, "status == {}", status),
+            Assertion::StatusIn { min, max } => write!(f, "status in {}..{}", min, max),
+            Assertion::HeaderExists { name } => write!(f, "header {} exists", name),
+            Assertion::HeaderEquals { name, value } => write!(f, "header {} == {}", name, value),
+            Assertion::HeaderMatches { name, pattern } => {
+                write!(f, "header {} ~ {}", name, pattern)
+            }
+            Assertion::JsonPathEquals { path, value } => {
+                write!(f, "jsonpath {} == {}", path, value)
+            }
+            Assertion::JsonPathContains { path, value } => {
+                write!(f, "jsonpath {} contains {}", path, value)
+            }
+            Assertion::JsonPathMatches { path, pattern } => {
+                write!(f, "jsonpath {} ~ {}", path, pattern)
+            }
+            Assertion::Schema { schema } => write!(f, "schema {}", schema),
+            Assertion::ResponseTime { max_ms } => write!(f, "time < {}", max_ms),
+        }
+    }
+}
+
+/// Parses the non-empty lines of the tests tab.
+pub fn parse_lines(text: &str) -> Vec<(String, Result<Assertion, String>)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| (line.to_string(), line.parse()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn parses_every_kind_of_assertion() {
+        assert_eq!(
+            "status == 200".parse(),
+            Ok(Assertion::StatusEquals { status: 200 })
+        );
+        assert_eq!(
+            "status in 200..299".parse(),
+            Ok(Assertion::StatusIn { min: 200, max: 299 })
+        );
+        assert_eq!(
+            "header Content-Type exists".parse(),
+            Ok(Assertion::HeaderExists {
+                name: "Content-Type".into()
+            })
+        );
+        assert_eq!(
+            "header Content-Type == application/json".parse(),
+            Ok(Assertion::HeaderEquals {
+                name: "Content-Type".into(),
+                value: "application/json".into()
+            })
+        );
+        assert_eq!(
+            "header Content-Type ~ json$".parse(),
+            Ok(Assertion::HeaderMatches {
+                name: "Content-Type".into(),
+                pattern: "json$".into()
+            })
+        );
+        assert_eq!(
+            "jsonpath $.name contains kanye".parse(),
+            Ok(Assertion::JsonPathContains {
+                path: "$.name".into(),
+                value: "kanye".into()
+            })
+        );
+        assert_eq!(
+            r#"schema {"type": "object"}"#.parse(),
+            Ok(Assertion::Schema {
+                schema: serde_json::json!({"type": "object"})
+            })
+        );
+        assert_eq!(
+            "time < 500ms".parse(),
+            Ok(Assertion::ResponseTime { max_ms: 500 })
+        );
+    }
+
+    #[test]
+    fn reads_json_values_and_falls_back_to_strings() {
+        assert_eq!(
+            "jsonpath $.id == 5".parse(),
+            Ok(Assertion::JsonPathEquals {
+                path: "$.id".into(),
+                value: Value::from(5)
+            })
+        );
+        assert_eq!(
+            "jsonpath $.name == kanye west".parse(),
+            Ok(Assertion::JsonPathEquals {
+                path: "$.name".into(),
+                value: Value::from("kanye west")
+            })
+        );
+    }
+
+    #[test]
+    fn explains_invalid_assertions() {
+        let error = |line: &str| line.parse::<Assertion>().unwrap_err();
+        assert_eq!(error("status == ok"), "expected a number, got ok");
+        assert_eq!(
+            error("status in 200"),
+            "expected a range like 200..299, got 200"
+        );
+        assert_eq!(error("status > 200"), "unknown status operator: >");
+        assert_eq!(error("header"), "missing header name");
+        assert_eq!(
+            error("header Accept is json"),
+            "unknown header operator: is"
+        );
+        assert!(error("jsonpath $.name ~ (").starts_with("invalid regex"));
+        assert!(error("jsonpath $.items[ == 1").starts_with("invalid JSONPath $.items["));
+        assert!(error("jsonpath name == 1").starts_with("invalid JSONPath name"));
+        assert!(error("schema {").starts_with("invalid schema"));
+        assert_eq!(
+            error("body contains x"),
+            "unknown assertion: body contains x"
+        );
+    }
+
+    #[test]
+    fn displays_assertions_as_they_are_written() {
+        for line in [
+            "status == 200",
+            "status in 200..299",
+            "header Accept exists",
+            "jsonpath $.id == 5",
+            r#"jsonpath $.name == "kanye""#,
+            "time < 500",
+        ] {
+            assert_eq!(line.parse::<Assertion>().unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn keeps_invalid_lines_and_skips_comments() {
+        let lines = parse_lines("status == 200\n\n  # a comment\n status == ok \n");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            (
+                "status == 200".into(),
+                Ok(Assertion::StatusEquals { status: 200 })
+            )
+        );
+        assert_eq!(lines[1].0, "status == ok");
+        assert!(lines[1].1.is_err());
+    }
+
+    fn response() -> Response {
+        Response {
+            status: 201,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: r#"{"id": 5, "name": "kanye west", "tags": ["a", "b"]}"#.into(),
+            elapsed: Duration::from_millis(120),
+            ..Default::default()
+        }
+    }
+
+    /// The message of a failed assertion, or `None` when it passed.
+    fn evaluate(line: &str) -> Option<String> {
+        let result = line.parse::<Assertion>().unwrap().evaluate(&response());
+        assert_eq!(result.passed, result.message.is_none());
+        result.message
+    }
+
+    #[test]
+    fn evaluates_status_assertions() {
+        assert_eq!(evaluate("status == 201"), None);
+        assert_eq!(evaluate("status in 200..299"), None);
+        assert_eq!(
+            evaluate("status == 200").as_deref(),
+            Some("expected status 200, got 201")
+        );
+        assert_eq!(
+            evaluate("status in 300..399").as_deref(),
+            Some("expected status in 300..399, got 201")
+        );
+    }
+
+    #[test]
+    fn evaluates_header_assertions_whatever_the_case_of_the_name() {
+        assert_eq!(evaluate("header content-type exists"), None);
+        assert_eq!(evaluate("header Content-Type == application/json"), None);
+        assert_eq!(evaluate("header Content-Type ~ json$"), None);
+        assert_eq!(
+            evaluate("header Accept exists").as_deref(),
+            Some("header Accept is missing")
+        );
+        assert_eq!(
+            evaluate("header Content-Type == text/html").as_deref(),
+            Some("expected header Content-Type to be text/html, got application/json")
+        );
+    }
+
+    #[test]
+    fn evaluates_jsonpath_assertions() {
+        assert_eq!(evaluate("jsonpath $.id == 5"), None);
+        assert_eq!(evaluate("jsonpath $.id == \"5\""), None);
+        assert_eq!(evaluate("jsonpath $.name == kanye west"), None);
+        assert_eq!(evaluate("jsonpath $.name contains kanye"), None);
+        assert_eq!(evaluate("jsonpath $.tags[1] ~ ^b$"), None);
+        assert_eq!(
+            evaluate("jsonpath $.id == 6").as_deref(),
+            Some("expected $.id to be 6, got 5")
+        );
+        assert_eq!(
+            evaluate("jsonpath $.missing == 1").as_deref(),
+            Some("nothing found at $.missing")
+        );
+
+        let result = "jsonpath $.id == 5"
+            .parse::<Assertion>()
+            .unwrap()
+            .evaluate(&Response {
+                body: "not json".into(),
+                ..response()
+            });
+        assert!(result.message.unwrap().starts_with("body is not JSON"));
+    }
+
+    #[test]
+    fn evaluates_schema_and_time_assertions() {
+        assert_eq!(
+            evaluate(r#"schema {"type": "object", "required": ["id", "name"]}"#),
+            None
+        );
+        assert_eq!(
+            evaluate(r#"schema {"properties": {"id": {"type": "string"}}}"#).as_deref(),
+            Some(r#"5 is not of type "string" at /id"#)
+        );
+        assert_eq!(evaluate("time < 500"), None);
+        assert_eq!(
+            evaluate("time < 100").as_deref(),
+            Some("expected response under 100ms, took 120ms")
+        );
+    }
+}
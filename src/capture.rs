@@ -0,0 +1,313 @@
+use std::{fmt, str::FromStr};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{environment::Variables, http::Response, jsonpath};
+
+/// Stores a value of the response into an environment variable after a successful send.
+///
+/// In the captures tab, captures are written one per line:
+///
+/// ```text
+/// token = jsonpath $.access_token
+/// session = header X-Session
+/// id = regex "id":\s*(\d+)
+/// sid = cookie SID
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    pub variable: String,
+    #[serde(flatten)]
+    pub source: CaptureSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "from", rename_all = "snake_case")]
+pub enum CaptureSource {
+    JsonPath {
+        path: String,
+    },
+    Header {
+        name: String,
+    },
+    /// The first capture group, or the whole match when the pattern has none.
+    Regex {
+        pattern: String,
+    },
+    Cookie {
+        name: String,
+    },
+}
+
+impl Capture {
+    pub fn extract(&self, response: &Response) -> Result<String, String> {
+        match &self.source {
+            CaptureSource::JsonPath { path } => jsonpath::select_str(&response.text()?, path)?
+                .first()
+                .map(jsonpath::text)
+                .ok_or_else(|| format!("nothing found at {}", path)),
+            CaptureSource::Header { name } => response
+                .headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+                .ok_or_else(|| format!("header {} is missing", name)),
+            CaptureSource::Regex { pattern } => {
+                let regex = Regex::new(pattern)
+                    .map_err(|err| format!("invalid regex {}: {}", pattern, err))?;
+                let body = response.text()?;
+                let captures = regex
+                    .captures(&body)
+                    .ok_or_else(|| format!("{} does not match the body", pattern))?;
+                Ok(captures
+                    .get(1)
+                    .or_else(|| captures.get(0))
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_default())
+            }
+            CaptureSource::Cookie { name } => response
+                .headers
+                .iter()
+                .filter(|(header, _)| header.eq_ignore_ascii_case("set-cookie"))
+                .filter_map(|(_, value)| value.split(';').next()?.split_once('='))
+                .find(|(cookie, _)| cookie.trim() == name)
+                .map(|(_, value)| value.trim().to_string())
+                .ok_or_else(|| format!("cookie {} is not set", name)),
+        }
+    }
+}
+
+/// Stores every capture into `variables`, returning a console line per capture.
+///
+/// The names of the variables captured are appended to `assigned`.
+pub fn apply(
+    captures: &[Capture],
+    response: &Response,
+    variables: &mut Variables,
+    assigned: &mut Vec<String>,
+) -> Vec<String> {
+    captures
+        .iter()
+        .map(|capture| match capture.extract(response) {
+            Ok(value) => {
+                variables.insert(capture.variable.clone(), value);
+                assigned.push(capture.variable.clone());
+                format!("captured {}", capture.variable)
+            }
+            Err(err) => format!("capture {} failed: {}", capture.variable, err),
+        })
+        .collect()
+}
+
+impl FromStr for Capture {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (variable, source) = line
+            .split_once('=')
+            .ok_or_else(|| format!("expected `name = source`, got {}", line.trim()))?;
+        let variable = variable.trim();
+        if variable.is_empty() {
+            return Err(String::from("missing variable name"));
+        }
+
+        let source = source.trim();
+        let (kind, value) = match source.find(char::is_whitespace) {
+            Some(i) => (&source[..i], source[i..].trim().to_string()),
+            None => (source, String::new()),
+        };
+        if value.is_empty() {
+            return Err(format!("missing {} argument", kind));
+        }
+
+        let source = match kind {
+            "jsonpath" => {
+                jsonpath::check(&value)?;
+                CaptureSource::JsonPath { path: value }
+            }
+            "header" => CaptureSource::Header { name: value },
+            "regex" => {
+                Regex::new(&value).map_err(|err| format!("invalid regex {}: {}", value, err))?;
+                CaptureSource::Regex { pattern: value }
+            }
+            "cookie" => CaptureSource::Cookie { name: value },
+            _ => return Err(format!("unknown capture source: {}", kind)),
+        };
+
+        Ok(Capture {
+            variable: variable.to_string(),
+            source,
+        })
+    }
+}
+
+impl fmt::Display for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            CaptureSource::JsonPath { path } => write!(f, "{} = jsonpath {}", self.variable, path),
+            CaptureSource::Header { name } => write!(f, "{} = header {}", self.variable, name),
+            CaptureSource::Regex { pattern } => write!(f, "{} = regex {}", self.variable, pattern),
+            CaptureSource::Cookie { name } => write!(f, "{} = cookie {}", self.variable, name),
+        }
+    }
+}
+
+/// Parses the non-empty lines of the captures tab.
+pub fn parse_lines(text: &str) -> Vec<(String, Result<Capture, String>)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| (line.to_string(), line.parse()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(variable: &str, source: CaptureSource) -> Capture {
+        Capture {
+            variable: variable.into(),
+            source,
+        }
+    }
+
+    fn response() -> Response {
+        Response {
+            headers: vec![
+                ("X-Session".into(), "abc".into()),
+                ("Set-Cookie".into(), "theme=dark; Path=/".into()),
+                ("Set-Cookie".into(), "SID=31d4; HttpOnly".into()),
+            ],
+            body: r#"{"access_token": "t0k", "id": 42}"#.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_every_source() {
+        assert_eq!(
+            "token = jsonpath $.access_token".parse(),
+            Ok(capture(
+                "token",
+                CaptureSource::JsonPath {
+                    path: "$.access_token".into()
+                }
+            ))
+        );
+        assert_eq!(
+            "session=header X-Session".parse(),
+            Ok(capture(
+                "session",
+                CaptureSource::Header {
+                    name: "X-Session".into()
+                }
+            ))
+        );
+        assert_eq!(
+            r#"id = regex "id":\s*(\d+)"#.parse(),
+            Ok(capture(
+                "id",
+                CaptureSource::Regex {
+                    pattern: r#""id":\s*(\d+)"#.into()
+                }
+            ))
+        );
+        assert_eq!(
+            "sid = cookie SID".parse(),
+            Ok(capture("sid", CaptureSource::Cookie { name: "SID".into() }))
+        );
+    }
+
+    #[test]
+    fn keeps_equals_signs_of_the_source() {
+        assert_eq!(
+            "pair = regex a=(\\w+)".parse(),
+            Ok(capture(
+                "pair",
+                CaptureSource::Regex {
+                    pattern: "a=(\\w+)".into()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn explains_invalid_captures() {
+        let error = |line: &str| line.parse::<Capture>().unwrap_err();
+        assert_eq!(
+            error("token jsonpath $.a"),
+            "expected `name = source`, got token jsonpath $.a"
+        );
+        assert_eq!(error(" = header X"), "missing variable name");
+        assert_eq!(error("token = header"), "missing header argument");
+        assert_eq!(error("token = body x"), "unknown capture source: body");
+        assert!(error("token = regex (").starts_with("invalid regex"));
+        assert!(error("token = jsonpath $.a[").starts_with("invalid JSONPath"));
+    }
+
+    #[test]
+    fn displays_captures_as_they_are_parsed() {
+        for line in [
+            "token = jsonpath $.access_token",
+            "session = header X-Session",
+            r#"id = regex "id":\s*(\d+)"#,
+            "sid = cookie SID",
+        ] {
+            let capture: Capture = line.parse().unwrap();
+            assert_eq!(capture.to_string(), line);
+            assert_eq!(capture.to_string().parse(), Ok(capture));
+        }
+    }
+
+    #[test]
+    fn keeps_invalid_lines_and_skips_comments() {
+        let lines = parse_lines("# captures\n\ntoken = jsonpath $.a\n  oops \n");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].0, "token = jsonpath $.a");
+        assert!(lines[0].1.is_ok());
+        assert_eq!(lines[1].0, "oops");
+        assert!(lines[1].1.is_err());
+    }
+
+    #[test]
+    fn extracts_values_from_the_response() {
+        let extract = |line: &str| line.parse::<Capture>().unwrap().extract(&response());
+        assert_eq!(extract("a = jsonpath $.access_token"), Ok("t0k".into()));
+        assert_eq!(extract("a = jsonpath $.id"), Ok("42".into()));
+        assert_eq!(extract("a = header x-session"), Ok("abc".into()));
+        assert_eq!(extract(r#"a = regex "id":\s*(\d+)"#), Ok("42".into()));
+        assert_eq!(extract(r#"a = regex "t\w+""#), Ok(r#""t0k""#.into()));
+        assert_eq!(extract("a = cookie SID"), Ok("31d4".into()));
+        assert_eq!(
+            extract("a = jsonpath $.missing"),
+            Err("nothing found at $.missing".into())
+        );
+        assert_eq!(
+            extract("a = cookie other"),
+            Err("cookie other is not set".into())
+        );
+    }
+
+    #[test]
+    fn stores_the_captured_values_only() {
+        let captures: Vec<Capture> = ["token = jsonpath $.access_token", "gone = header Missing"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let mut variables = Variables::new();
+        let mut assigned = Vec::new();
+
+        let log = apply(&captures, &response(), &mut variables, &mut assigned);
+        assert_eq!(
+            log,
+            [
+                "captured token",
+                "capture gone failed: header Missing is missing"
+            ]
+        );
+        assert_eq!(variables, Variables::from([("token".into(), "t0k".into())]));
+        assert_eq!(assigned, ["token"]);
+    }
+}
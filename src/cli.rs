@@ -0,0 +1,156 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::{
+    app::AppResult,
+    collection::Collection,
+    config::Config,
+    cookies::CookieJar,
+    http::HttpClient,
+    report::{self, Reporter, ReporterKind},
+    runner::{self, RunOptions, Runner},
+};
+
+/// Command line arguments.
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    /// Collection file to open in the user interface.
+    pub collection: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the requests of a collection without the user interface.
+    Run(RunArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Collection file to run.
+    pub collection: PathBuf,
+
+    /// Folder to run, as a `/` separated path of names.
+    #[arg(short, long)]
+    pub folder: Option<String>,
+
+    /// Environment to use, defaults to the first one of the collection.
+    #[arg(short, long)]
+    pub env: Option<String>,
+
+    /// Number of iterations, defaults to the number of data rows.
+    #[arg(short = 'n', long)]
+    pub iterations: Option<usize>,
+
+    /// CSV or JSON file whose rows are bound to variables on each iteration.
+    #[arg(short, long)]
+    pub data: Option<PathBuf>,
+
+    /// Delay between requests in milliseconds.
+    #[arg(long, default_value_t = 0)]
+    pub delay: u64,
+
+    /// Stop the run at the first failed request.
+    #[arg(long)]
+    pub bail: bool,
+
+    /// Request timeout in milliseconds, overriding the config file.
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Write the run summary as JSON to this file.
+    #[arg(long)]
+    pub export: Option<PathBuf>,
+
+    /// Report writer as `NAME[=FILE]`, one of cli, json or junit. Can be repeated.
+    #[arg(short, long, default_value = "cli")]
+    pub reporter: Vec<Reporter>,
+}
+
+/// Runs a collection headlessly, returning whether every request passed.
+pub async fn run(args: RunArgs) -> AppResult<bool> {
+    let collection = Collection::load(&args.collection)?;
+    let path = match &args.folder {
+        Some(folder) => collection
+            .resolve(folder)
+            .ok_or_else(|| format!("folder not found: {}", folder))?,
+        None => Vec::new(),
+    };
+
+    let mut options = RunOptions {
+        delay: Duration::from_millis(args.delay),
+        stop_on_failure: args.bail,
+        ..Default::default()
+    };
+
+    let dir = args.collection.parent().unwrap_or(&args.collection);
+    let data = args
+        .data
+        .or_else(|| collection.data_file(&path).map(|data| dir.join(data)));
+    if let Some(data) = data {
+        options.data = runner::load_data(&data)?;
+    }
+    options.iterations = args.iterations.unwrap_or(options.data.len().max(1));
+
+    let variables = collection.variables(args.env.as_deref())?;
+    let environment = args
+        .env
+        .clone()
+        .or_else(|| collection.environments.first().map(|env| env.name.clone()))
+        .unwrap_or_else(|| String::from("default"));
+    let jar_path = CookieJar::path(&collection.name, &environment);
+    let cookies = match &jar_path {
+        Some(path) => Arc::new(CookieJar::load(path)?),
+        None => Arc::default(),
+    };
+
+    let mut settings = Config::load()?.client;
+    if let Some(env) = collection.environment(args.env.as_deref())? {
+        settings.apply_environment(env);
+    }
+    if let Some(timeout) = args.timeout {
+        settings.timeout_ms = timeout;
+    }
+
+    let mut runner = Runner::new(
+        HttpClient::new(cookies.clone(), settings)?,
+        runner::collect(&collection, &path),
+        variables,
+        options,
+    );
+
+    // Results are streamed when the terminal report goes to standard output.
+    let live = args
+        .reporter
+        .iter()
+        .any(|reporter| reporter.kind == ReporterKind::Cli && reporter.output.is_none());
+
+    runner.start();
+    while runner.is_running() {
+        tokio::time::sleep_until(runner.next_at().into()).await;
+        if let Some(result) = runner.step().await {
+            if live {
+                println!("{}", report::result_line(result));
+            }
+        }
+    }
+
+    if let Some(path) = jar_path {
+        cookies.save(&path)?;
+    }
+
+    let summary = runner.summary();
+    for reporter in &args.reporter {
+        reporter.write(&summary)?;
+    }
+
+    if let Some(export) = args.export {
+        summary.export(&export)?;
+    }
+
+    Ok(summary.failed == 0)
+}
@@ -0,0 +1,46 @@
+use std::{
+    env,
+    io::{self, Write},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::app::AppResult;
+
+/// The system clipboard, opened on first use and kept open so copied text outlives the call.
+#[derive(Default)]
+pub struct Clipboard(Option<arboard::Clipboard>);
+
+impl std::fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Clipboard").field(&self.0.is_some()).finish()
+    }
+}
+
+impl Clipboard {
+    /// Copies text to the system clipboard, or asks the terminal to with OSC 52 over SSH or when
+    /// there is no system clipboard.
+    pub fn copy(&mut self, text: &str) -> AppResult<()> {
+        if env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some() {
+            return osc52(text);
+        }
+        self.system(text).or_else(|_| osc52(text))
+    }
+
+    fn system(&mut self, text: &str) -> AppResult<()> {
+        let clipboard = match &mut self.0 {
+            Some(clipboard) => clipboard,
+            None => self.0.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+}
+
+/// Sets the clipboard of the terminal the user interface is drawn on.
+fn osc52(text: &str) -> AppResult<()> {
+    let mut out = io::stderr();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()?;
+    Ok(())
+}
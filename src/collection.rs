@@ -0,0 +1,282 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use tui_tree_widget::TreeItem;
+
+use crate::{
+    app::AppResult,
+    assertion::Assertion,
+    capture::Capture,
+    component::urlbar::Method,
+    environment::{Environment, Variables},
+    items::Item,
+};
+
+/// A tree of saved requests, stored as a JSON file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    #[serde(default)]
+    pub environments: Vec<Environment>,
+    #[serde(default)]
+    pub items: Vec<CollectionItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CollectionItem {
+    Folder(Folder),
+    Request(SavedRequest),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Folder {
+    pub name: String,
+    /// CSV or JSON file whose rows are bound to variables when running the folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<PathBuf>,
+    /// Scripts run around every request of the folder.
+    #[serde(default, skip_serializing_if = "Scripts::is_empty")]
+    pub scripts: Scripts,
+    #[serde(default)]
+    pub items: Vec<CollectionItem>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedRequest {
+    pub name: String,
+    #[serde(default)]
+    pub method: Method,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<Header>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub captures: Vec<Capture>,
+    #[serde(default, skip_serializing_if = "Scripts::is_empty")]
+    pub scripts: Scripts,
+    /// Neither send nor store cookies for this request.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable_cookies: bool,
+    /// Recent filters of the response, the latest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<String>,
+}
+
+/// Rhai scripts run before sending a request and after receiving its response.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scripts {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pre_request: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub post_response: String,
+}
+
+impl Scripts {
+    pub fn is_empty(&self) -> bool {
+        self.pre_request.is_empty() && self.post_response.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+impl std::fmt::Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
+
+impl FromStr for Header {
+    type Err = String;
+
+    /// Parses a `Name: value` line.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        match line.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => Ok(Header {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            _ => Err(format!("`{}` is not a `Name: value` header", line)),
+        }
+    }
+}
+
+impl CollectionItem {
+    pub fn name(&self) -> &str {
+        match self {
+            CollectionItem::Folder(folder) => &folder.name,
+            CollectionItem::Request(request) => &request.name,
+        }
+    }
+
+    pub fn children(&self) -> &[CollectionItem] {
+        match self {
+            CollectionItem::Folder(folder) => &folder.items,
+            CollectionItem::Request(_) => &[],
+        }
+    }
+}
+
+impl Collection {
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Returns the item at the given index path, as used by the sidebar tree.
+    pub fn get(&self, path: &[usize]) -> Option<&CollectionItem> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.items.get(*first)?, |item, &i| item.children().get(i))
+    }
+
+    pub fn get_mut(&mut self, path: &[usize]) -> Option<&mut CollectionItem> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.items.get_mut(*first)?, |item, &i| match item {
+                CollectionItem::Folder(folder) => folder.items.get_mut(i),
+                CollectionItem::Request(_) => None,
+            })
+    }
+
+    /// Data file configured on the folder at the given index path.
+    pub fn data_file(&self, path: &[usize]) -> Option<&Path> {
+        match self.get(path)? {
+            CollectionItem::Folder(folder) => folder.data.as_deref(),
+            CollectionItem::Request(_) => None,
+        }
+    }
+
+    /// Scripts of the folders containing the item at the given index path, outermost first.
+    pub fn folder_scripts(&self, path: &[usize]) -> Vec<Scripts> {
+        let mut items = self.items.as_slice();
+        let mut scripts = Vec::new();
+
+        for &i in path {
+            match items.get(i) {
+                Some(CollectionItem::Folder(folder)) => {
+                    scripts.push(folder.scripts.clone());
+                    items = &folder.items;
+                }
+                _ => break,
+            }
+        }
+
+        scripts
+    }
+
+    /// Resolves a `/` separated path of item names into an index path.
+    pub fn resolve(&self, path: &str) -> Option<Vec<usize>> {
+        let mut items = self.items.as_slice();
+        let mut indices = Vec::new();
+
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            let i = items.iter().position(|item| item.name() == name)?;
+            indices.push(i);
+            items = items[i].children();
+        }
+
+        Some(indices)
+    }
+
+    /// The named environment, or the first one when no name is given.
+    pub fn environment(&self, name: Option<&str>) -> AppResult<Option<&Environment>> {
+        match name {
+            Some(name) => Ok(Some(
+                self.environments
+                    .iter()
+                    .find(|env| env.name == name)
+                    .ok_or_else(|| format!("environment not found: {}", name))?,
+            )),
+            None => Ok(self.environments.first()),
+        }
+    }
+
+    /// Variables of the named environment, or of the first one when no name is given.
+    pub fn variables(&self, environment: Option<&str>) -> AppResult<Variables> {
+        Ok(self
+            .environment(environment)?
+            .map(|env| env.variables.clone())
+            .unwrap_or_default())
+    }
+
+    /// The `/` separated names of the items down to the given index path.
+    pub fn path_name(&self, path: &[usize]) -> Option<String> {
+        let mut items = self.items.as_slice();
+        let mut names = Vec::new();
+
+        for &i in path {
+            let item = items.get(i)?;
+            names.push(item.name());
+            items = item.children();
+        }
+
+        Some(names.join("/"))
+    }
+
+    /// Index paths of all requests, with their `/` separated path of names.
+    pub fn requests(&self) -> Vec<(Vec<usize>, String)> {
+        let mut requests = Vec::new();
+        collect_requests(&self.items, &[], "", &mut requests);
+        requests
+    }
+
+    pub fn tree(&self) -> Vec<TreeItem<'static, Item>> {
+        tree_items(&self.items, &[])
+    }
+}
+
+fn collect_requests(
+    items: &[CollectionItem],
+    parent: &[usize],
+    prefix: &str,
+    requests: &mut Vec<(Vec<usize>, String)>,
+) {
+    for (i, item) in items.iter().enumerate() {
+        let path = [parent, &[i]].concat();
+        let name = format!("{}{}", prefix, item.name());
+
+        match item {
+            CollectionItem::Folder(folder) => {
+                collect_requests(&folder.items, &path, &format!("{}/", name), requests)
+            }
+            CollectionItem::Request(_) => requests.push((path, name)),
+        }
+    }
+}
+
+fn tree_items(items: &[CollectionItem], parent: &[usize]) -> Vec<TreeItem<'static, Item>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let path = [parent, &[i]].concat();
+            let inner = Item::with_path(item.name(), path.clone());
+
+            match item {
+                CollectionItem::Folder(folder) => {
+                    TreeItem::new(inner, tree_items(&folder.items, &path))
+                }
+                CollectionItem::Request(_) => TreeItem::new_leaf(inner),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use strum::IntoEnumIterator;
+
+use crate::http::{ClientSettings, HttpVersion};
+
+#[derive(Debug, Default, Clone, Copy, strum::Display, strum::EnumIter, PartialEq)]
+pub enum ClientField {
+    #[default]
+    #[strum(serialize = "Timeout (ms)")]
+    Timeout,
+    #[strum(serialize = "Connect timeout (ms)")]
+    ConnectTimeout,
+    #[strum(serialize = "Follow redirects")]
+    FollowRedirects,
+    #[strum(serialize = "Max redirects")]
+    MaxRedirects,
+    #[strum(serialize = "User-Agent")]
+    UserAgent,
+    #[strum(serialize = "gzip")]
+    Gzip,
+    #[strum(serialize = "brotli")]
+    Brotli,
+    #[strum(serialize = "deflate")]
+    Deflate,
+    #[strum(serialize = "HTTP version")]
+    HttpVersion,
+    #[strum(serialize = "Preview cap (bytes)")]
+    PreviewLimit,
+}
+
+/// Client settings view state. Changes are applied when the view closes.
+#[derive(Debug, Default)]
+pub struct ClientBar {
+    pub settings: ClientSettings,
+    pub selected: usize,
+    /// Value being typed for the selected field.
+    pub input: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ClientBar {
+    pub fn new(settings: ClientSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+
+    pub fn field(&self) -> ClientField {
+        ClientField::iter().nth(self.selected).unwrap_or_default()
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < ClientField::iter().count() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn value(&self, field: ClientField) -> String {
+        let settings = &self.settings;
+        let timeout = |ms: u64| match ms {
+            0 => String::from("none"),
+            ms => ms.to_string(),
+        };
+
+        match field {
+            ClientField::Timeout => timeout(settings.timeout_ms),
+            ClientField::ConnectTimeout => timeout(settings.connect_timeout_ms),
+            ClientField::FollowRedirects => settings.follow_redirects.to_string(),
+            ClientField::MaxRedirects => settings.max_redirects.to_string(),
+            ClientField::UserAgent => settings.user_agent.clone(),
+            ClientField::Gzip => settings.gzip.to_string(),
+            ClientField::Brotli => settings.brotli.to_string(),
+            ClientField::Deflate => settings.deflate.to_string(),
+            ClientField::HttpVersion => settings.http_version.to_string(),
+            ClientField::PreviewLimit => settings.preview_limit.to_string(),
+        }
+    }
+
+    /// Toggles or cycles the selected field, or starts typing its value.
+    pub fn change(&mut self) {
+        let field = self.field();
+        let settings = &mut self.settings;
+
+        match field {
+            ClientField::FollowRedirects => settings.follow_redirects = !settings.follow_redirects,
+            ClientField::Gzip => settings.gzip = !settings.gzip,
+            ClientField::Brotli => settings.brotli = !settings.brotli,
+            ClientField::Deflate => settings.deflate = !settings.deflate,
+            ClientField::HttpVersion => {
+                settings.http_version = match settings.http_version {
+                    HttpVersion::Auto => HttpVersion::Http1Only,
+                    HttpVersion::Http1Only => HttpVersion::Http2PriorKnowledge,
+                    HttpVersion::Http2PriorKnowledge => HttpVersion::Auto,
+                }
+            }
+            ClientField::Timeout => self.input = Some(settings.timeout_ms.to_string()),
+            ClientField::ConnectTimeout => {
+                self.input = Some(settings.connect_timeout_ms.to_string())
+            }
+            ClientField::MaxRedirects => self.input = Some(settings.max_redirects.to_string()),
+            ClientField::UserAgent => self.input = Some(settings.user_agent.clone()),
+            ClientField::PreviewLimit => self.input = Some(settings.preview_limit.to_string()),
+        }
+    }
+
+    /// Stores the typed value into the selected field.
+    pub fn confirm(&mut self) {
+        let Some(input) = self.input.take() else {
+            return;
+        };
+        let input = input.trim();
+        let number = || {
+            input
+                .parse::<u64>()
+                .map_err(|_| format!("`{}` is not a number", input))
+        };
+
+        let res = match self.field() {
+            ClientField::Timeout => number().map(|n| self.settings.timeout_ms = n),
+            ClientField::ConnectTimeout => number().map(|n| self.settings.connect_timeout_ms = n),
+            ClientField::MaxRedirects => number().map(|n| self.settings.max_redirects = n as usize),
+            ClientField::PreviewLimit => number().map(|n| self.settings.preview_limit = n),
+            ClientField::UserAgent => {
+                self.settings.user_agent = input.to_string();
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        self.message = res.err();
+    }
+}
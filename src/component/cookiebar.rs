@@ -0,0 +1,79 @@
+use crate::cookies::CookieEntry;
+
+/// Cookie manager view state.
+#[derive(Debug, Default)]
+pub struct CookieBar {
+    pub cookies: Vec<CookieEntry>,
+    pub selected: usize,
+    /// Cookie being added or edited, as `domain path name=value`.
+    pub input: Option<String>,
+    /// Cookie replaced when the input is confirmed.
+    pub editing: Option<CookieEntry>,
+    pub message: Option<String>,
+}
+
+impl CookieBar {
+    pub fn new(cookies: Vec<CookieEntry>) -> Self {
+        Self {
+            cookies,
+            ..Default::default()
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.cookies.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn selected(&self) -> Option<&CookieEntry> {
+        self.cookies.get(self.selected)
+    }
+
+    /// Replaces the listed cookies, keeping the selection in range.
+    pub fn refresh(&mut self, cookies: Vec<CookieEntry>) {
+        self.cookies = cookies;
+        self.selected = self.selected.min(self.cookies.len().saturating_sub(1));
+    }
+
+    pub fn add(&mut self) {
+        self.editing = None;
+        self.input = Some(String::from("example.com / name=value"));
+    }
+
+    pub fn edit(&mut self) {
+        if let Some(cookie) = self.selected().cloned() {
+            self.input = Some(format!(
+                "{} {} {}={}",
+                cookie.domain, cookie.path, cookie.name, cookie.value
+            ));
+            self.editing = Some(cookie);
+        }
+    }
+
+    /// Parses the input into a domain, path, name and value.
+    pub fn parse_input(&self) -> Result<(String, String, String, String), String> {
+        let input = self.input.as_deref().unwrap_or_default().trim();
+        let mut words = input.splitn(3, char::is_whitespace);
+
+        match (words.next(), words.next(), words.next()) {
+            (Some(domain), Some(path), Some(cookie)) if path.starts_with('/') => {
+                let (name, value) = cookie
+                    .trim()
+                    .split_once('=')
+                    .ok_or_else(|| String::from("expected name=value"))?;
+                Ok((
+                    domain.to_string(),
+                    path.to_string(),
+                    name.trim().to_string(),
+                    value.trim().to_string(),
+                ))
+            }
+            _ => Err(String::from("expected `domain /path name=value`")),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use serde_json::Value;
+use tui_tree_widget::TreeItem;
+
+use crate::{
+    items::{Item, StatefulTree},
+    jsonpath,
+};
+
+/// A JSON response shown as a collapsible tree.
+#[derive(Debug, Default)]
+pub struct JsonTree {
+    pub value: Value,
+    pub tree: StatefulTree<'static>,
+}
+
+impl JsonTree {
+    pub fn new(value: Value) -> Self {
+        let items = match &value {
+            Value::Object(map) => map.iter().map(|(key, value)| item(key, value)).collect(),
+            Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| item(&format!("[{}]", i), value))
+                .collect(),
+            value => vec![item("$", value)],
+        };
+
+        let mut tree = StatefulTree::with_items(items);
+        tree.first();
+        JsonTree { value, tree }
+    }
+
+    /// The selected value and its JSONPath.
+    pub fn selected(&self) -> Option<(&Value, String)> {
+        let indices = self.tree.state.selected();
+        if indices.is_empty() {
+            return None;
+        }
+        if !matches!(self.value, Value::Object(_) | Value::Array(_)) {
+            return Some((&self.value, "$".to_string()));
+        }
+
+        indices.iter().try_fold(
+            (&self.value, "$".to_string()),
+            |(value, path), &i| match value {
+                Value::Object(map) => map
+                    .iter()
+                    .nth(i)
+                    .map(|(key, value)| (value, format!("{}{}", path, segment(key)))),
+                Value::Array(values) => values
+                    .get(i)
+                    .map(|value| (value, format!("{}[{}]", path, i))),
+                _ => None,
+            },
+        )
+    }
+
+    /// Text copied for the selected value, strings without their quotes.
+    pub fn selected_text(&self) -> Option<String> {
+        self.selected().map(|(value, _)| match value {
+            Value::Object(_) | Value::Array(_) => {
+                serde_json::to_string_pretty(value).unwrap_or_default()
+            }
+            value => jsonpath::text(value),
+        })
+    }
+}
+
+fn item(name: &str, value: &Value) -> TreeItem<'static, Item> {
+    let label = match value {
+        Value::Object(map) => format!("[object] {} ({} keys)", name, map.len()),
+        Value::Array(values) => format!("[array] {} ({} items)", name, values.len()),
+        Value::String(_) => format!("[string] {}: {}", name, value),
+        Value::Number(n) => format!("[number] {}: {}", name, n),
+        Value::Bool(b) => format!("[bool] {}: {}", name, b),
+        Value::Null => format!("[null] {}", name),
+    };
+
+    match value {
+        Value::Object(map) => TreeItem::new(
+            Item::new(&label),
+            map.iter().map(|(key, value)| item(key, value)).collect(),
+        ),
+        Value::Array(values) => TreeItem::new(
+            Item::new(&label),
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| item(&format!("[{}]", i), value))
+                .collect(),
+        ),
+        _ => TreeItem::new_leaf(Item::new(&label)),
+    }
+}
+
+/// Path segment of an object key, in dot notation when the key allows it.
+fn segment(key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    match plain {
+        true => format!(".{}", key),
+        false => format!("['{}']", key.replace('\\', "\\\\").replace('\'', "\\'")),
+    }
+}
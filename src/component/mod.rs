@@ -1,5 +1,10 @@
+pub mod clientbar;
+pub mod cookiebar;
+pub mod jsontree;
+pub mod palettebar;
 pub mod requestbar;
 pub mod responsebar;
+pub mod runnerbar;
 pub mod sidebar;
 pub mod tabbar;
 pub mod urlbar;
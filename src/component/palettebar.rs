@@ -0,0 +1,127 @@
+use crate::keymap::Action;
+
+/// What choosing an entry of the command palette does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Action(Action),
+    /// Opens the request at this collection path in a tab.
+    Request(Vec<usize>),
+    /// Makes the environment at this index the active one.
+    Environment(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub label: String,
+    /// Keys bound to the entry, if any.
+    pub keys: String,
+    pub command: Command,
+}
+
+impl Entry {
+    pub fn kind(&self) -> &'static str {
+        match self.command {
+            Command::Action(_) => "action",
+            Command::Request(_) => "request",
+            Command::Environment(_) => "environment",
+        }
+    }
+}
+
+/// Command palette view state.
+#[derive(Debug, Default)]
+pub struct PaletteBar {
+    pub entries: Vec<Entry>,
+    pub query: String,
+    /// Entries matching the query, best first, with the positions of their matched characters.
+    pub matches: Vec<(usize, Vec<usize>)>,
+    pub selected: usize,
+}
+
+impl PaletteBar {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let mut palettebar = Self {
+            entries,
+            ..Default::default()
+        };
+        palettebar.refresh();
+        palettebar
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refresh();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.refresh();
+    }
+
+    pub fn selected(&self) -> Option<&Entry> {
+        self.matches
+            .get(self.selected)
+            .and_then(|(i, _)| self.entries.get(*i))
+    }
+
+    /// Matches the entries against the query again, selecting the best one.
+    fn refresh(&mut self) {
+        let mut matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                fuzzy_match(&self.query, &entry.label)
+                    .map(|(score, positions)| (score, i, positions))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, i, _)| (-score, self.entries[*i].label.len(), *i));
+
+        self.matches = matches
+            .into_iter()
+            .map(|(_, i, positions)| (i, positions))
+            .collect();
+        self.selected = 0;
+    }
+}
+
+/// Scores how well `text` matches the characters of `query` in order, ignoring case.
+///
+/// Matches that are consecutive or start a word score higher. Returns the score with the
+/// positions of the matched characters, or nothing when some character is missing.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let text = text.chars().collect::<Vec<_>>();
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut start = 0;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().collect::<String>();
+        let found =
+            (start..text.len()).find(|&i| text[i].to_lowercase().collect::<String>() == q)?;
+
+        score += 1;
+        if positions.last().is_some_and(|&last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(text[found - 1], ' ' | '/' | '_' | '-' | '.') {
+            score += 3;
+        }
+        score -= (found - start).min(3) as i64;
+
+        positions.push(found);
+        start = found + 1;
+    }
+
+    Some((score, positions))
+}
@@ -1,27 +1,220 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::{assertion::AssertionResult, component::urlbar::InputMode, vim::Vim};
+
 #[derive(Debug, Default)]
 pub struct RequestBar {
     pub body: String,
+    pub tests: String,
+    pub captures: String,
+    pub pre_request: String,
+    pub post_response: String,
+    pub test_results: Vec<AssertionResult>,
     pub request_menu: RequestMenu,
+    pub input_mode: InputMode,
+    pub cursor_position: usize,
+    pub disable_cookies: bool,
+    /// First line shown of the edited text.
+    pub scroll: u16,
+    pub vim: Vim,
 }
 
 impl RequestBar {
     pub fn left(&mut self) {
+        self.cursor_position = 0;
+        self.scroll = 0;
+        self.vim.reset();
         self.request_menu = match self.request_menu {
-            RequestMenu::Params => RequestMenu::Body,
+            RequestMenu::Params => RequestMenu::PostResponse,
             RequestMenu::Authentication => RequestMenu::Params,
             RequestMenu::Headers => RequestMenu::Authentication,
             RequestMenu::Body => RequestMenu::Headers,
+            RequestMenu::Tests => RequestMenu::Body,
+            RequestMenu::Captures => RequestMenu::Tests,
+            RequestMenu::PreRequest => RequestMenu::Captures,
+            RequestMenu::PostResponse => RequestMenu::PreRequest,
         }
     }
 
     pub fn right(&mut self) {
+        self.cursor_position = 0;
+        self.scroll = 0;
+        self.vim.reset();
         self.request_menu = match self.request_menu {
             RequestMenu::Params => RequestMenu::Authentication,
             RequestMenu::Authentication => RequestMenu::Headers,
             RequestMenu::Headers => RequestMenu::Body,
-            RequestMenu::Body => RequestMenu::Params,
+            RequestMenu::Body => RequestMenu::Tests,
+            RequestMenu::Tests => RequestMenu::Captures,
+            RequestMenu::Captures => RequestMenu::PreRequest,
+            RequestMenu::PreRequest => RequestMenu::PostResponse,
+            RequestMenu::PostResponse => RequestMenu::Params,
         }
     }
+
+    pub fn select(&mut self, request_menu: RequestMenu) {
+        self.cursor_position = 0;
+        self.scroll = 0;
+        self.vim.reset();
+        self.request_menu = request_menu;
+    }
+
+    pub fn scroll_by(&mut self, lines: isize) {
+        let last = self
+            .text()
+            .unwrap_or_default()
+            .lines()
+            .count()
+            .saturating_sub(1);
+        self.scroll = (self.scroll as usize)
+            .saturating_add_signed(lines)
+            .min(last) as u16;
+    }
+
+    /// Text edited in the selected tab, if the tab is editable.
+    pub fn text(&self) -> Option<&str> {
+        match self.request_menu {
+            RequestMenu::Body => Some(&self.body),
+            RequestMenu::Tests => Some(&self.tests),
+            RequestMenu::Captures => Some(&self.captures),
+            RequestMenu::PreRequest => Some(&self.pre_request),
+            RequestMenu::PostResponse => Some(&self.post_response),
+            _ => None,
+        }
+    }
+
+    /// Cursor column and row inside the edited text.
+    pub fn cursor(&self) -> (u16, u16) {
+        let before = &self.text().unwrap_or_default()[..self.cursor_position];
+        let row = before.matches('\n').count();
+        let col = before
+            .rsplit('\n')
+            .next()
+            .map(|line| line.chars().count())
+            .unwrap_or_default();
+
+        (col as u16, row as u16)
+    }
+
+    /// Passes a key to vim editing, returning whether it was a vim command.
+    pub fn vim(&mut self, key_event: KeyEvent) -> bool {
+        let text = match self.request_menu {
+            RequestMenu::Body => &mut self.body,
+            RequestMenu::Tests => &mut self.tests,
+            RequestMenu::Captures => &mut self.captures,
+            RequestMenu::PreRequest => &mut self.pre_request,
+            RequestMenu::PostResponse => &mut self.post_response,
+            _ => return false,
+        };
+        self.vim.handle(
+            key_event,
+            text,
+            &mut self.cursor_position,
+            &mut self.input_mode,
+        )
+    }
+
+    pub fn redo(&mut self) {
+        let text = match self.request_menu {
+            RequestMenu::Body => &mut self.body,
+            RequestMenu::Tests => &mut self.tests,
+            RequestMenu::Captures => &mut self.captures,
+            RequestMenu::PreRequest => &mut self.pre_request,
+            RequestMenu::PostResponse => &mut self.post_response,
+            _ => return,
+        };
+        self.vim.redo(text, &mut self.cursor_position);
+    }
+
+    /// Inserts text at the cursor of the edited text.
+    pub fn insert(&mut self, text: &str) {
+        let field = match self.request_menu {
+            RequestMenu::Body => &mut self.body,
+            RequestMenu::Tests => &mut self.tests,
+            RequestMenu::Captures => &mut self.captures,
+            RequestMenu::PreRequest => &mut self.pre_request,
+            RequestMenu::PostResponse => &mut self.post_response,
+            _ => return,
+        };
+        field.insert_str(self.cursor_position, text);
+        self.cursor_position += text.len();
+    }
+
+    pub fn edit(&mut self, key_event: KeyEvent) {
+        let (text, cursor) = match self.request_menu {
+            RequestMenu::Body => (&mut self.body, &mut self.cursor_position),
+            RequestMenu::Tests => (&mut self.tests, &mut self.cursor_position),
+            RequestMenu::Captures => (&mut self.captures, &mut self.cursor_position),
+            RequestMenu::PreRequest => (&mut self.pre_request, &mut self.cursor_position),
+            RequestMenu::PostResponse => (&mut self.post_response, &mut self.cursor_position),
+            _ => return,
+        };
+
+        let line_start = text[..*cursor].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[*cursor..]
+            .find('\n')
+            .map_or(text.len(), |i| *cursor + i);
+
+        match key_event.code {
+            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Char(c) => {
+                text.insert(*cursor, c);
+                *cursor += c.len_utf8();
+            }
+            KeyCode::Enter => {
+                text.insert(*cursor, '\n');
+                *cursor += 1;
+            }
+            KeyCode::Tab => {
+                text.insert_str(*cursor, "  ");
+                *cursor += 2;
+            }
+            KeyCode::Backspace => {
+                if let Some(c) = text[..*cursor].chars().next_back() {
+                    *cursor -= c.len_utf8();
+                    text.remove(*cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if *cursor < text.len() {
+                    text.remove(*cursor);
+                }
+            }
+            KeyCode::Left => {
+                if let Some(c) = text[..*cursor].chars().next_back() {
+                    *cursor -= c.len_utf8();
+                }
+            }
+            KeyCode::Right => {
+                if let Some(c) = text[*cursor..].chars().next() {
+                    *cursor += c.len_utf8();
+                }
+            }
+            KeyCode::Up if line_start > 0 => {
+                let col = text[line_start..*cursor].chars().count();
+                let prev_start = text[..line_start - 1].rfind('\n').map_or(0, |i| i + 1);
+                *cursor = column(text, prev_start, line_start - 1, col);
+            }
+            KeyCode::Down if line_end < text.len() => {
+                let col = text[line_start..*cursor].chars().count();
+                let next_end = text[line_end + 1..]
+                    .find('\n')
+                    .map_or(text.len(), |i| line_end + 1 + i);
+                *cursor = column(text, line_end + 1, next_end, col);
+            }
+            KeyCode::Home => *cursor = line_start,
+            KeyCode::End => *cursor = line_end,
+            _ => {}
+        }
+    }
+}
+
+/// Byte offset of the `col`th character of the line spanning `start..end`.
+fn column(text: &str, start: usize, end: usize, col: usize) -> usize {
+    text[start..end]
+        .char_indices()
+        .nth(col)
+        .map_or(end, |(i, _)| start + i)
 }
 
 #[derive(Debug, Default, strum::Display, strum::EnumIter, PartialEq)]
@@ -31,4 +224,10 @@ pub enum RequestMenu {
     Authentication,
     Headers,
     Body,
+    Tests,
+    Captures,
+    #[strum(serialize = "Pre-request")]
+    PreRequest,
+    #[strum(serialize = "Post-response")]
+    PostResponse,
 }
@@ -1,4 +1,305 @@
+use crate::{
+    component::jsontree::JsonTree,
+    content,
+    format::{Format, StyledLine},
+    http::Response,
+    jsonpath,
+};
+
+/// Bytes shown on each row of the hex view.
+pub const HEX_WIDTH: usize = 16;
+
+/// Filters remembered for each request.
+pub const RECENT_FILTERS: usize = 10;
+
 #[derive(Debug, Default)]
 pub struct ResponseBar {
     pub body: String,
+    pub response: Option<Response>,
+    pub response_menu: ResponseMenu,
+    /// File the response is being saved to, while it is typed.
+    pub save_path: Option<String>,
+    /// First row shown in the hex view.
+    pub hex_scroll: usize,
+    /// Offset jumped to, highlighted in the hex view.
+    pub hex_offset: Option<usize>,
+    /// Offset being typed to jump to.
+    pub offset_input: Option<String>,
+    /// Format chosen by hand instead of the detected one.
+    pub format: Option<Format>,
+    /// The body in the current format.
+    pub formatted: Vec<StyledLine>,
+    /// First line shown in the body view.
+    pub body_scroll: usize,
+    /// Line a selection of the body started on, and the line it reaches.
+    pub selection: Option<(usize, usize)>,
+    /// The whole body as a tree, when it is JSON.
+    pub json: Option<JsonTree>,
+    /// Why there is no tree of the body.
+    pub json_error: String,
+    /// JSONPath or jq-like expression the body is filtered with.
+    pub filter: String,
+    /// Whether the filter is being typed.
+    pub filtering: bool,
+    /// Why the filter could not be applied.
+    pub filter_error: Option<String>,
+    /// Filters used before on the request, the latest first.
+    pub recent_filters: Vec<String>,
+    /// Recent filter shown while browsing them.
+    pub recent_index: Option<usize>,
+    /// Name of the response header being typed to copy its value.
+    pub header_input: Option<String>,
+}
+
+impl ResponseBar {
+    pub fn left(&mut self) {
+        self.response_menu = match self.response_menu {
+            ResponseMenu::Body => ResponseMenu::Timeline,
+            ResponseMenu::Tree => ResponseMenu::Body,
+            ResponseMenu::Hex => ResponseMenu::Tree,
+            ResponseMenu::Timeline => ResponseMenu::Hex,
+        }
+    }
+
+    pub fn right(&mut self) {
+        self.response_menu = match self.response_menu {
+            ResponseMenu::Body => ResponseMenu::Tree,
+            ResponseMenu::Tree => ResponseMenu::Hex,
+            ResponseMenu::Hex => ResponseMenu::Timeline,
+            ResponseMenu::Timeline => ResponseMenu::Body,
+        }
+    }
+
+    /// Value of a header of the last response, whatever the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.response.as_ref().and_then(|res| {
+            res.headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name.trim()))
+                .map(|(_, value)| value.as_str())
+        })
+    }
+
+    /// First and last selected lines of the body.
+    pub fn selected_lines(&self) -> Option<(usize, usize)> {
+        self.selection
+            .map(|(anchor, end)| (anchor.min(end), anchor.max(end)))
+    }
+
+    /// Starts selecting lines from the first one in view, or stops.
+    pub fn toggle_selection(&mut self) {
+        self.selection = match self.selection {
+            Some(_) => None,
+            None => Some((self.body_scroll, self.body_scroll)),
+        };
+    }
+
+    /// Moves the end of the selection by `lines`, scrolling to keep it among the `height` in view.
+    pub fn extend_selection(&mut self, lines: isize, height: usize) {
+        let last = self.formatted.len().saturating_sub(1);
+        if let Some((_, end)) = &mut self.selection {
+            *end = end.saturating_add_signed(lines).min(last);
+            if *end < self.body_scroll {
+                self.body_scroll = *end;
+            } else if *end >= self.body_scroll + height.max(1) {
+                self.body_scroll = *end + 1 - height.max(1);
+            }
+        }
+    }
+
+    /// Text of the selected lines of the body.
+    pub fn region(&self) -> Option<String> {
+        let (first, last) = self.selected_lines()?;
+        let text = self
+            .formatted
+            .iter()
+            .skip(first)
+            .take(last - first + 1)
+            .map(|line| {
+                line.iter()
+                    .map(|(_, text)| text.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(text)
+    }
+
+    /// Shows a new response, in the hex view when it is binary.
+    pub fn set_response(&mut self, response: Response) {
+        self.body = response.body.clone();
+        self.hex_scroll = 0;
+        self.hex_offset = None;
+        self.body_scroll = 0;
+        // Only a preview of large bodies is kept, parse the whole body from its file.
+        let json = match response.binary {
+            true => Err(String::from("the body is not JSON")),
+            false => response.text().and_then(|body| {
+                serde_json::from_str(&body).map_err(|_| String::from("the body is not JSON"))
+            }),
+        };
+        (self.json, self.json_error) = match json {
+            Ok(value) => (Some(JsonTree::new(value)), String::new()),
+            Err(err) => (None, err),
+        };
+        if response.binary && self.response_menu != ResponseMenu::Timeline {
+            self.response_menu = ResponseMenu::Hex;
+        }
+        self.response = Some(response);
+        self.reformat();
+    }
+
+    /// Format of the body, picked from its content type unless chosen by hand.
+    pub fn body_format(&self) -> Format {
+        self.format.unwrap_or_else(|| match &self.response {
+            Some(res) => Format::detect(content::mime(&res.headers).as_deref(), &res.body),
+            None => Format::Raw,
+        })
+    }
+
+    pub fn cycle_format(&mut self) {
+        self.format = Format::cycle(self.format);
+        self.reformat();
+    }
+
+    fn reformat(&mut self) {
+        self.selection = None;
+        self.filter_error = None;
+        if !self.filter.trim().is_empty() {
+            match self.filtered() {
+                Ok(formatted) => {
+                    self.formatted = formatted;
+                    return;
+                }
+                Err(err) => {
+                    self.filter_error = Some(err.split_whitespace().collect::<Vec<_>>().join(" "))
+                }
+            }
+        }
+        self.formatted = self.body_format().apply(&self.body);
+    }
+
+    /// The values of the body matching the filter.
+    fn filtered(&self) -> Result<Vec<StyledLine>, String> {
+        let json = self.json.as_ref().ok_or_else(|| self.json_error.clone())?;
+        let values = jsonpath::select(&json.value, &jsonpath::normalize(&self.filter))?;
+        let text = serde_json::to_string_pretty(&values).map_err(|err| err.to_string())?;
+        Ok(Format::Json.apply(&text))
+    }
+
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.body_scroll = 0;
+        self.reformat();
+    }
+
+    pub fn filter_push(&mut self, c: char) {
+        let mut filter = self.filter.clone();
+        filter.push(c);
+        self.recent_index = None;
+        self.set_filter(filter);
+    }
+
+    pub fn filter_pop(&mut self) {
+        let mut filter = self.filter.clone();
+        filter.pop();
+        self.recent_index = None;
+        self.set_filter(filter);
+    }
+
+    /// Steps through the recent filters, towards older ones when `older` is set.
+    pub fn recall(&mut self, older: bool) {
+        if self.recent_filters.is_empty() {
+            return;
+        }
+        let last = self.recent_filters.len() - 1;
+        self.recent_index = match (self.recent_index, older) {
+            (None, true) => Some(0),
+            (None, false) => None,
+            (Some(i), true) => Some((i + 1).min(last)),
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+        };
+        let filter = match self.recent_index {
+            Some(i) => self.recent_filters[i].clone(),
+            None => String::new(),
+        };
+        self.set_filter(filter);
+    }
+
+    /// Puts the filter first among the recent ones, if it is valid.
+    pub fn remember_filter(&mut self) {
+        self.filtering = false;
+        self.recent_index = None;
+        let filter = self.filter.trim().to_string();
+        if filter.is_empty() || self.filter_error.is_some() {
+            return;
+        }
+        self.recent_filters.retain(|recent| *recent != filter);
+        self.recent_filters.insert(0, filter);
+        self.recent_filters.truncate(RECENT_FILTERS);
+    }
+
+    pub fn scroll_body(&mut self, lines: isize) {
+        let last = self.formatted.len().saturating_sub(1);
+        self.body_scroll = self.body_scroll.saturating_add_signed(lines).min(last);
+    }
+
+    /// Bytes of the response kept in memory.
+    pub fn bytes(&self) -> &[u8] {
+        self.response
+            .as_ref()
+            .map(|res| res.raw.preview.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn hex_rows(&self) -> usize {
+        self.bytes().len().div_ceil(HEX_WIDTH)
+    }
+
+    pub fn scroll_down(&mut self, rows: usize) {
+        self.hex_scroll = (self.hex_scroll + rows).min(self.hex_rows().saturating_sub(1));
+    }
+
+    pub fn scroll_up(&mut self, rows: usize) {
+        self.hex_scroll = self.hex_scroll.saturating_sub(rows);
+    }
+
+    /// Scrolls to the offset typed, as decimal or as `0x` prefixed hex.
+    pub fn jump(&mut self) -> Result<(), String> {
+        let Some(input) = self.offset_input.take() else {
+            return Ok(());
+        };
+        let input = input.trim();
+
+        let offset = match input
+            .strip_prefix("0x")
+            .or_else(|| input.strip_prefix("0X"))
+        {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => input.parse(),
+        }
+        .map_err(|_| format!("`{}` is not an offset", input))?;
+
+        if offset >= self.bytes().len() {
+            return Err(format!(
+                "offset {:#x} is past the end ({:#x} bytes)",
+                offset,
+                self.bytes().len()
+            ));
+        }
+
+        self.hex_offset = Some(offset);
+        self.hex_scroll = offset / HEX_WIDTH;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, strum::Display, strum::EnumIter, PartialEq)]
+pub enum ResponseMenu {
+    #[default]
+    Body,
+    Tree,
+    Hex,
+    Timeline,
 }
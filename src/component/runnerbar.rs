@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::runner::{Runner, StepOutcome};
+
+/// Collection runner view state.
+#[derive(Debug)]
+pub struct RunnerBar {
+    pub runner: Runner,
+    pub scroll: usize,
+    pub message: Option<String>,
+    /// The request of the run being sent in the background.
+    pub pending: Option<JoinHandle<StepOutcome>>,
+}
+
+impl RunnerBar {
+    pub fn new(runner: Runner) -> Self {
+        Self {
+            runner,
+            scroll: 0,
+            message: None,
+            pending: None,
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.scroll + 1 < self.runner.results.len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn more_iterations(&mut self) {
+        self.runner.options.iterations += 1;
+    }
+
+    pub fn less_iterations(&mut self) {
+        if self.runner.options.iterations > 1 {
+            self.runner.options.iterations -= 1;
+        }
+    }
+
+    pub fn more_delay(&mut self) {
+        self.runner.options.delay += Duration::from_millis(100);
+    }
+
+    pub fn less_delay(&mut self) {
+        self.runner.options.delay = self
+            .runner
+            .options
+            .delay
+            .saturating_sub(Duration::from_millis(100));
+    }
+
+    pub fn toggle_stop_on_failure(&mut self) {
+        self.runner.options.stop_on_failure = !self.runner.options.stop_on_failure;
+    }
+}
@@ -8,7 +8,7 @@ pub struct TabBar {
 
 impl TabBar {
     pub fn right(&mut self) {
-        if self.selected < self.tabs.len() - 1 {
+        if self.selected + 1 < self.tabs.len() {
             self.selected += 1;
         }
     }
@@ -27,7 +27,7 @@ impl TabBar {
     }
 
     pub fn last(&mut self) {
-        self.selected = self.tabs.len() - 1;
+        self.selected = self.tabs.len().saturating_sub(1);
     }
 
     pub fn add(&mut self, item: Item) {
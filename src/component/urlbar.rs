@@ -1,6 +1,11 @@
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use tui_menu::{MenuItem, MenuState};
 
+use crossterm::event::KeyEvent;
+
+use crate::{collection::SavedRequest, vim::Vim};
+
 #[derive(Debug)]
 pub struct UrlBar {
     pub title: String,
@@ -9,6 +14,7 @@ pub struct UrlBar {
     pub input_mode: InputMode,
     pub method: Method,
     pub method_menu: MenuState<Method>,
+    pub vim: Vim,
 }
 
 impl Default for UrlBar {
@@ -25,18 +31,49 @@ impl Default for UrlBar {
                     .map(|m| MenuItem::item(m.to_string(), m))
                     .collect(),
             )]),
+            vim: Vim::default(),
         }
     }
 }
 
+impl UrlBar {
+    pub fn load(&mut self, request: &SavedRequest) {
+        self.title = request.name.clone();
+        self.text = request.url.clone();
+        self.cursor_position = 0;
+        self.input_mode = InputMode::Normal;
+        self.method = request.method.clone();
+        self.method_menu.set_child_name(0, self.method.to_string());
+        self.vim.reset();
+    }
+
+    /// Passes a key to vim editing, returning whether it was a vim command.
+    pub fn vim(&mut self, key_event: KeyEvent) -> bool {
+        self.vim.handle(
+            key_event,
+            &mut self.text,
+            &mut self.cursor_position,
+            &mut self.input_mode,
+        )
+    }
+
+    pub fn redo(&mut self) {
+        self.vim.redo(&mut self.text, &mut self.cursor_position);
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub enum InputMode {
     #[default]
     Normal,
     Insert,
+    Visual,
 }
 
-#[derive(Debug, Default, Clone, strum::Display, strum::EnumIter)]
+#[derive(
+    Debug, Default, Clone, PartialEq, strum::Display, strum::EnumIter, Serialize, Deserialize,
+)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum Method {
     #[default]
     Get,
@@ -47,3 +84,17 @@ pub enum Method {
     Head,
     Options,
 }
+
+impl From<&Method> for reqwest::Method {
+    fn from(method: &Method) -> Self {
+        match method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Head => reqwest::Method::HEAD,
+            Method::Options => reqwest::Method::OPTIONS,
+        }
+    }
+}
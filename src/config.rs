@@ -0,0 +1,128 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{app::AppResult, http::ClientSettings, keymap::KeyConfig, theme::ThemeConfig};
+
+/// User configuration, read from `config.toml` in the config directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub client: ClientSettings,
+    /// Key bindings replacing the default ones.
+    #[serde(skip_serializing_if = "KeyConfig::is_empty")]
+    pub keys: KeyConfig,
+    /// Name of a built-in theme or of one in `themes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub themes: BTreeMap<String, ThemeConfig>,
+}
+
+/// How the request and response panes are arranged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Orientation {
+    /// Above each other when there is room, beside each other otherwise.
+    #[default]
+    Auto,
+    /// Beside each other.
+    Horizontal,
+    /// Above each other.
+    Vertical,
+}
+
+impl Orientation {
+    pub fn next(self) -> Self {
+        match self {
+            Orientation::Auto => Orientation::Horizontal,
+            Orientation::Horizontal => Orientation::Vertical,
+            Orientation::Vertical => Orientation::Auto,
+        }
+    }
+}
+
+/// Pane sizes and arrangement, saved to `layout.toml` in the data directory when the
+/// application quits, apart from the hand-written config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutState {
+    pub show_sidebar: bool,
+    /// Width of the sidebar in columns.
+    pub sidebar: u16,
+    /// Percentage of the lower panes given to the request pane.
+    pub split: u16,
+    pub orientation: Orientation,
+}
+
+impl Default for LayoutState {
+    fn default() -> Self {
+        Self {
+            show_sidebar: true,
+            sidebar: 25,
+            split: 50,
+            orientation: Orientation::Auto,
+        }
+    }
+}
+
+impl LayoutState {
+    /// Loads the saved layout, using the defaults when none was saved yet.
+    pub fn load() -> AppResult<Self> {
+        Ok(read_state("layout.toml")?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> AppResult<()> {
+        write_state("layout.toml", self)
+    }
+}
+
+/// Client settings changed in the client settings popup, saved to `client.toml` in the data
+/// directory. While the file exists they replace the `[client]` table of the config file.
+pub fn save_client(settings: &ClientSettings) -> AppResult<()> {
+    write_state("client.toml", settings)
+}
+
+/// Path of a file the application writes in the data directory.
+fn state_path(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("neoman").join(name))
+}
+
+fn read_state<T: DeserializeOwned>(name: &str) -> AppResult<Option<T>> {
+    match state_path(name) {
+        Some(path) if path.exists() => Ok(Some(toml::from_str(&fs::read_to_string(path)?)?)),
+        _ => Ok(None),
+    }
+}
+
+fn write_state<T: Serialize>(name: &str, state: &T) -> AppResult<()> {
+    let Some(path) = state_path(name) else {
+        return Err("no data directory".into());
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, toml::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("neoman").join("config.toml"))
+    }
+
+    /// Loads the configuration, using the defaults when there is no file yet, with the client
+    /// settings saved from the application over it.
+    pub fn load() -> AppResult<Self> {
+        let mut config: Config = match Self::path() {
+            Some(path) if path.exists() => toml::from_str(&fs::read_to_string(path)?)?,
+            _ => Self::default(),
+        };
+        if let Some(client) = read_state("client.toml")? {
+            config.client = client;
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,107 @@
+use encoding_rs::{Encoding, UTF_8};
+
+/// Content type of a response, without its parameters, e.g. `text/html`.
+pub fn mime(headers: &[(String, String)]) -> Option<String> {
+    content_type(headers).map(|value| {
+        value
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+    })
+}
+
+fn content_type(headers: &[(String, String)]) -> Option<&str> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str())
+}
+
+/// The `charset` parameter of the content type.
+fn charset(headers: &[(String, String)]) -> Option<String> {
+    content_type(headers)?
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+/// Whether a body is binary, going by its content type and then by its first bytes.
+pub fn is_binary(headers: &[(String, String)], bytes: &[u8]) -> bool {
+    let mime = mime(headers).unwrap_or_default();
+    let (kind, subtype) = mime.split_once('/').unwrap_or((&mime, ""));
+
+    let text = kind == "text"
+        || subtype.ends_with("+json")
+        || subtype.ends_with("+xml")
+        || matches!(
+            subtype,
+            "json"
+                | "xml"
+                | "javascript"
+                | "ecmascript"
+                | "x-www-form-urlencoded"
+                | "graphql"
+                | "yaml"
+                | "x-yaml"
+                | "toml"
+                | "csv"
+                | "x-ndjson"
+        );
+    let binary = matches!(kind, "image" | "audio" | "video" | "font")
+        || matches!(
+            subtype,
+            "octet-stream"
+                | "pdf"
+                | "zip"
+                | "gzip"
+                | "x-protobuf"
+                | "protobuf"
+                | "grpc"
+                | "wasm"
+                | "msgpack"
+                | "x-msgpack"
+                | "cbor"
+        );
+
+    match (text, binary) {
+        (true, _) => false,
+        (_, true) => true,
+        _ if charset(headers).is_some() => false,
+        _ => sniff(bytes),
+    }
+}
+
+/// Guesses from the first bytes: NUL bytes, many control characters or invalid UTF-8 mean binary.
+fn sniff(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(1024)];
+    if head.contains(&0) {
+        return true;
+    }
+
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0c | 0x1b))
+        .count();
+    if control * 10 > head.len() {
+        return true;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        // A character cut at the end of the sniffed bytes is still text.
+        Err(err) => err.error_len().is_some(),
+    }
+}
+
+/// Decodes a text body with the charset of its content type, or its byte order mark, or UTF-8.
+pub fn decode(headers: &[(String, String)], bytes: &[u8]) -> String {
+    let encoding = charset(headers)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
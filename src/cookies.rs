@@ -0,0 +1,146 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use cookie_store::{CookieExpiration, CookieStore, RawCookie};
+use reqwest::{header::HeaderValue, Url};
+
+use crate::app::AppResult;
+
+/// A cookie as listed in the cookie manager.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CookieEntry {
+    pub domain: String,
+    pub path: String,
+    pub name: String,
+    pub value: String,
+    pub expires: String,
+}
+
+/// Cookie jar shared by every request of an environment.
+#[derive(Debug, Default)]
+pub struct CookieJar(RwLock<CookieStore>);
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|value| std::str::from_utf8(value.as_bytes()).ok())
+            .filter_map(|value| RawCookie::parse(value.to_string()).ok());
+
+        self.0.write().unwrap().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let cookies = self
+            .0
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        match cookies.is_empty() {
+            true => None,
+            false => HeaderValue::from_str(&cookies).ok(),
+        }
+    }
+}
+
+impl CookieJar {
+    /// File the cookies of an environment are persisted to.
+    pub fn path(collection: &str, environment: &str) -> Option<PathBuf> {
+        let name = |text: &str| -> String {
+            text.chars()
+                .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+                    true => c,
+                    false => '_',
+                })
+                .collect()
+        };
+
+        dirs::data_dir().map(|dir| {
+            dir.join("neoman")
+                .join("cookies")
+                .join(name(collection))
+                .join(format!("{}.json", name(environment)))
+        })
+    }
+
+    /// Loads a jar, starting empty when the file does not exist yet.
+    pub fn load(path: &Path) -> AppResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        let store = CookieStore::load_json_all(reader)
+            .map_err(|err| format!("invalid cookie jar {}: {}", path.display(), err))?;
+        Ok(Self(RwLock::new(store)))
+    }
+
+    /// Saves every cookie, including session ones, so sessions survive restarts.
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.0
+            .read()
+            .unwrap()
+            .save_incl_expired_and_nonpersistent_json(&mut writer)
+            .map_err(|err| format!("failed to save cookies: {}", err))?;
+        Ok(())
+    }
+
+    /// Lists the unexpired cookies, sorted by domain.
+    pub fn list(&self) -> Vec<CookieEntry> {
+        let mut cookies: Vec<CookieEntry> = self
+            .0
+            .read()
+            .unwrap()
+            .iter_unexpired()
+            .map(|cookie| CookieEntry {
+                domain: String::from(&cookie.domain),
+                path: String::from(&cookie.path),
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                expires: match &cookie.expires {
+                    CookieExpiration::AtUtc(at) => at.to_string(),
+                    CookieExpiration::SessionEnd => String::from("session"),
+                },
+            })
+            .collect();
+
+        cookies.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+        cookies
+    }
+
+    /// Adds a cookie, replacing any cookie with the same domain, path and name.
+    pub fn set(&self, domain: &str, path: &str, name: &str, value: &str) -> AppResult<()> {
+        let url = Url::parse(&format!(
+            "https://{}{}",
+            domain.trim_start_matches('.'),
+            path
+        ))?;
+        let cookie = RawCookie::build(name.to_string(), value.to_string())
+            .domain(domain.to_string())
+            .path(path.to_string())
+            .finish();
+
+        self.0
+            .write()
+            .unwrap()
+            .insert_raw(&cookie, &url)
+            .map_err(|err| format!("invalid cookie: {}", err))?;
+        Ok(())
+    }
+
+    pub fn remove(&self, domain: &str, path: &str, name: &str) {
+        self.0.write().unwrap().remove(domain, path, name);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use tempfile::TempPath;
+
+use crate::app::AppResult;
+
+/// Live counters of a response body being downloaded.
+#[derive(Debug, Default)]
+pub struct Progress {
+    received: AtomicU64,
+    /// Content length announced by the server, 0 when unknown.
+    total: AtomicU64,
+}
+
+impl Progress {
+    pub fn received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        match self.total.load(Ordering::Relaxed) {
+            0 => None,
+            total => Some(total),
+        }
+    }
+}
+
+/// A response body, kept in memory up to a limit and spilled to a temporary file past it.
+#[derive(Debug, Clone, Default)]
+pub struct Body {
+    /// The first bytes of the body, all of them when it was not truncated.
+    pub preview: Vec<u8>,
+    pub size: u64,
+    /// The whole body, removed when the last clone is dropped.
+    pub file: Option<Arc<TempPath>>,
+}
+
+impl Body {
+    pub fn is_truncated(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Writes the whole body to `path`.
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        match &self.file {
+            Some(file) => fs::copy(file.as_ref(), path).map(|_| ())?,
+            None => fs::write(path, &self.preview)?,
+        }
+        Ok(())
+    }
+}
+
+/// Reads a response chunk by chunk, keeping at most `limit` bytes in memory.
+pub async fn download(
+    mut res: reqwest::Response,
+    limit: u64,
+    progress: &Progress,
+) -> AppResult<Body> {
+    progress.received.store(0, Ordering::Relaxed);
+    progress
+        .total
+        .store(res.content_length().unwrap_or_default(), Ordering::Relaxed);
+
+    let mut body = Body::default();
+    let mut spill: Option<(File, TempPath)> = None;
+
+    while let Some(chunk) = res.chunk().await? {
+        body.size += chunk.len() as u64;
+        progress
+            .received
+            .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+
+        if spill.is_none() && body.size > limit {
+            let (file, path) = tempfile::Builder::new()
+                .prefix("neoman-")
+                .suffix(".body")
+                .tempfile()?
+                .into_parts();
+            let mut file = file;
+            file.write_all(&body.preview)?;
+            spill = Some((file, path));
+        }
+
+        match &mut spill {
+            Some((file, _)) => {
+                file.write_all(&chunk)?;
+                let room = limit.saturating_sub(body.preview.len() as u64) as usize;
+                body.preview
+                    .extend_from_slice(&chunk[..room.min(chunk.len())]);
+            }
+            None => body.preview.extend_from_slice(&chunk),
+        }
+    }
+
+    if let Some((mut file, path)) = spill {
+        file.flush()?;
+        body.file = Some(Arc::new(path));
+    }
+
+    Ok(body)
+}
+
+/// Formats a byte count like `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", size, UNITS[unit]),
+    }
+}
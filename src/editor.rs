@@ -0,0 +1,86 @@
+use std::{env, fs, path::Path, process::Command};
+
+use crate::app::AppResult;
+
+/// What a text opened in the editor is read back into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Url,
+    /// The text of the selected request tab.
+    Request,
+    Headers,
+}
+
+/// A text to open in the editor.
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub target: Target,
+    pub text: String,
+    /// Extension of the temporary file, for the editor to pick a syntax.
+    pub extension: &'static str,
+}
+
+/// Extension matching the content of a request body.
+pub fn extension(body: &str) -> &'static str {
+    let body = body.trim_start();
+    if serde_json::from_str::<serde_json::Value>(body).is_ok() {
+        "json"
+    } else if body.starts_with('<') {
+        "xml"
+    } else {
+        "txt"
+    }
+}
+
+/// The editor set in `$VISUAL` or `$EDITOR`, `vi` otherwise.
+pub fn command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+/// Writes the text to a temporary file, waits for the editor to close and reads it back.
+pub fn run(edit: &Edit) -> AppResult<String> {
+    let file = tempfile::Builder::new()
+        .prefix("neoman-")
+        .suffix(&format!(".{}", edit.extension))
+        .tempfile()?;
+    fs::write(file.path(), &edit.text)?;
+
+    let command = command();
+    let status = editor_command(&command, file.path())
+        .status()
+        .map_err(|err| format!("cannot run `{}`: {}", command, err))?;
+    if !status.success() {
+        return Err(format!("`{}` exited with {}", command, status).into());
+    }
+
+    let text = fs::read_to_string(file.path())?;
+    // Editors end files with a newline the text did not have.
+    Ok(match edit.text.ends_with('\n') {
+        true => text,
+        false => text.strip_suffix('\n').unwrap_or(&text).to_string(),
+    })
+}
+
+/// Runs the editor through the shell, like git does, so it can carry quoted arguments.
+#[cfg(unix)]
+fn editor_command(editor: &str, path: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("neoman")
+        .arg(path);
+    command
+}
+
+#[cfg(not(unix))]
+fn editor_command(editor: &str, path: &Path) -> Command {
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("notepad"));
+    command.args(words).arg(path);
+    command
+}
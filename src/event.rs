@@ -1,11 +1,12 @@
 use crate::app::AppResult;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -15,6 +16,10 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Text pasted into the terminal.
+    Paste(String),
+    /// A request sent in the background finished.
+    Wake,
 }
 
 /// Terminal event handler.
@@ -27,6 +32,10 @@ pub struct EventHandler {
     receiver: mpsc::Receiver<Event>,
     /// Event handler thread.
     handler: thread::JoinHandle<()>,
+    /// Whether the thread should stop reading the terminal.
+    paused: Arc<AtomicBool>,
+    /// Told by the thread once it stopped reading.
+    parked: mpsc::Receiver<()>,
 }
 
 impl EventHandler {
@@ -34,11 +43,23 @@ impl EventHandler {
     pub fn new(tick_rate: u64) -> Self {
         let tick_rate = Duration::from_millis(tick_rate);
         let (sender, receiver) = mpsc::channel();
+        let paused = Arc::new(AtomicBool::new(false));
+        let (park, parked) = mpsc::channel();
         let handler = {
             let sender = sender.clone();
+            let paused = paused.clone();
             thread::spawn(move || {
                 let mut last_tick = Instant::now();
                 loop {
+                    if paused.load(Ordering::Acquire) {
+                        let _ = park.send(());
+                        while paused.load(Ordering::Acquire) {
+                            thread::sleep(Duration::from_millis(10));
+                        }
+                        last_tick = Instant::now();
+                        continue;
+                    }
+
                     let timeout = tick_rate
                         .checked_sub(last_tick.elapsed())
                         .unwrap_or(tick_rate);
@@ -49,6 +70,7 @@ impl EventHandler {
                             CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
 
                             CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
+                            CrosstermEvent::Paste(text) => sender.send(Event::Paste(text)),
                             // TODO: Unimplemented
                             e => {
                                 tracing::debug!("event not implemented: {:#?}", e);
@@ -69,9 +91,26 @@ impl EventHandler {
             sender,
             receiver,
             handler,
+            paused,
+            parked,
         }
     }
 
+    /// A sender for events that do not come from the terminal.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
+
+    /// Stops reading the terminal, so another program can, and waits until the thread has.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Release);
+        let _ = self.parked.recv();
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Release);
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
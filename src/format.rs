@@ -0,0 +1,640 @@
+use std::iter;
+
+/// How a response body is shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Raw,
+    Json,
+    Xml,
+    Html,
+    /// Readable text of an html page, without its tags.
+    #[strum(serialize = "html text")]
+    HtmlText,
+}
+
+impl Format {
+    /// Picks a format from the content type, falling back to the look of the body.
+    pub fn detect(mime: Option<&str>, body: &str) -> Self {
+        let mime = mime.unwrap_or_default();
+        let start = body.trim_start().get(..15).unwrap_or(body.trim_start());
+        let start = start.to_ascii_lowercase();
+
+        if mime.contains("json") {
+            Self::Json
+        } else if mime.contains("html") {
+            Self::Html
+        } else if mime.contains("xml") {
+            Self::Xml
+        } else if (start.starts_with('{') || start.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(body).is_ok()
+        {
+            Self::Json
+        } else if start.starts_with("<!doctype html") || start.starts_with("<html") {
+            Self::Html
+        } else if start.starts_with('<') {
+            Self::Xml
+        } else {
+            Self::Raw
+        }
+    }
+
+    /// The next format for a manual override, `None` going back to the detected one.
+    pub fn cycle(format: Option<Self>) -> Option<Self> {
+        match format {
+            None => Some(Self::Raw),
+            Some(Self::Raw) => Some(Self::Json),
+            Some(Self::Json) => Some(Self::Xml),
+            Some(Self::Xml) => Some(Self::Html),
+            Some(Self::Html) => Some(Self::HtmlText),
+            Some(Self::HtmlText) => None,
+        }
+    }
+
+    pub fn apply(self, body: &str) -> Vec<StyledLine> {
+        match self {
+            Self::Raw => plain(body),
+            Self::Json => match serde_json::from_str::<serde_json::Value>(body) {
+                Ok(value) => plain(&serde_json::to_string_pretty(&value).unwrap_or_default()),
+                Err(_) => plain(body),
+            },
+            Self::Xml => markup(body, false),
+            Self::Html => markup(body, true),
+            Self::HtmlText => plain(&html_text(body)),
+        }
+    }
+}
+
+/// Kind of a piece of formatted text, styled by the user interface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Highlight {
+    Text,
+    Tag,
+    Attribute,
+    Value,
+    Comment,
+    Declaration,
+}
+
+pub type StyledLine = Vec<(Highlight, String)>;
+
+fn plain(text: &str) -> Vec<StyledLine> {
+    text.lines()
+        .map(|line| vec![(Highlight::Text, line.to_string())])
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open {
+        name: &'a str,
+        attributes: &'a str,
+        closed: bool,
+    },
+    Close(&'a str),
+    Comment(&'a str),
+    /// Doctypes, processing instructions and CDATA sections.
+    Declaration(&'a str),
+    Text(&'a str),
+}
+
+/// Elements without content in html.
+const VOID: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose content is not markup in html.
+const RAW_TEXT: [&str; 2] = ["script", "style"];
+
+/// Splits markup into tokens, tolerating malformed input.
+fn tokenize(text: &str, html: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+            rest = &rest[start..];
+        }
+
+        let (token, len) = if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|i| i + 3).unwrap_or(rest.len());
+            (Token::Comment(&rest[..end]), end)
+        } else if rest.starts_with("<![CDATA[") {
+            let end = rest.find("]]>").map(|i| i + 3).unwrap_or(rest.len());
+            (Token::Declaration(&rest[..end]), end)
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = rest.find('>').map(|i| i + 1).unwrap_or(rest.len());
+            (Token::Declaration(&rest[..end]), end)
+        } else {
+            let end = tag_end(rest);
+            let inner = rest[1..end].trim_end_matches('>');
+            match inner.strip_prefix('/') {
+                Some(name) => (Token::Close(name.trim()), end),
+                None => {
+                    let closed = inner.ends_with('/');
+                    let inner = inner.trim_end_matches('/');
+                    let split = inner
+                        .find(|c: char| c.is_whitespace())
+                        .unwrap_or(inner.len());
+                    let (name, attributes) = inner.split_at(split);
+                    (
+                        Token::Open {
+                            name,
+                            attributes: attributes.trim(),
+                            closed,
+                        },
+                        end,
+                    )
+                }
+            }
+        };
+
+        // `<` not starting a tag is text.
+        let raw = match &token {
+            Token::Open { name, .. } | Token::Close(name) => {
+                name.is_empty() || !name.starts_with(|c: char| c.is_alphabetic())
+            }
+            _ => false,
+        };
+        if raw {
+            tokens.push(Token::Text(&rest[..1]));
+            rest = &rest[1..];
+            continue;
+        }
+
+        let raw_text = match &token {
+            Token::Open { name, closed, .. } if html && !closed => RAW_TEXT
+                .iter()
+                .find(|raw| name.eq_ignore_ascii_case(raw))
+                .copied(),
+            _ => None,
+        };
+        tokens.push(token);
+        rest = &rest[len..];
+
+        if let Some(name) = raw_text {
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&format!("</{}", name))
+                .unwrap_or(rest.len());
+            if end > 0 {
+                tokens.push(Token::Text(&rest[..end]));
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    tokens
+}
+
+/// Length of the tag at the start of `text`, skipping `>` inside quoted values.
+fn tag_end(text: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn tag(name: &str, attributes: &str, close: bool, closed: bool) -> StyledLine {
+    let mut line = vec![(
+        Highlight::Tag,
+        format!("<{}{}", if close { "/" } else { "" }, name),
+    )];
+
+    let mut rest = attributes.trim();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        line.push((Highlight::Attribute, format!(" {}", &rest[..name_end])));
+        rest = rest[name_end..].trim_start();
+
+        if let Some(value) = rest.strip_prefix('=') {
+            let value = value.trim_start();
+            let end = match value.chars().next() {
+                Some(q @ ('"' | '\'')) => value[1..].find(q).map(|i| i + 2).unwrap_or(value.len()),
+                _ => value
+                    .find(|c: char| c.is_whitespace())
+                    .unwrap_or(value.len()),
+            };
+            line.push((Highlight::Tag, String::from("=")));
+            line.push((Highlight::Value, value[..end].to_string()));
+            rest = value[end..].trim_start();
+        }
+    }
+
+    line.push((
+        Highlight::Tag,
+        String::from(if closed { "/>" } else { ">" }),
+    ));
+    line
+}
+
+/// Pretty prints and highlights xml, or html when `html` is set.
+fn markup(text: &str, html: bool) -> Vec<StyledLine> {
+    let tokens = tokenize(text, html);
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let indent = |depth: usize| (Highlight::Text, "  ".repeat(depth));
+
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Open {
+                name,
+                attributes,
+                closed,
+            } => {
+                let void = html && VOID.iter().any(|void| name.eq_ignore_ascii_case(void));
+                let mut line = vec![indent(depth)];
+                line.extend(tag(name, attributes, false, *closed));
+
+                // Short text content stays on the line of its element.
+                if let (Some(Token::Text(text)), Some(Token::Close(close))) =
+                    (tokens.get(i + 1), tokens.get(i + 2))
+                {
+                    if close.eq_ignore_ascii_case(name) && !text.trim().contains('\n') {
+                        line.push((Highlight::Text, text.trim().to_string()));
+                        line.extend(tag(close, "", true, false));
+                        lines.push(line);
+                        i += 3;
+                        continue;
+                    }
+                }
+                if let Some(Token::Close(close)) = tokens.get(i + 1) {
+                    if close.eq_ignore_ascii_case(name) {
+                        line.extend(tag(close, "", true, false));
+                        lines.push(line);
+                        i += 2;
+                        continue;
+                    }
+                }
+
+                lines.push(line);
+                if !closed && !void {
+                    depth += 1;
+                }
+            }
+            Token::Close(name) => {
+                depth = depth.saturating_sub(1);
+                let mut line = vec![indent(depth)];
+                line.extend(tag(name, "", true, false));
+                lines.push(line);
+            }
+            Token::Comment(text) | Token::Declaration(text) => {
+                let highlight = match tokens[i] {
+                    Token::Comment(_) => Highlight::Comment,
+                    _ => Highlight::Declaration,
+                };
+                for part in text.lines() {
+                    lines.push(vec![indent(depth), (highlight, part.trim().to_string())]);
+                }
+            }
+            Token::Text(text) => {
+                for part in text.lines().map(str::trim).filter(|part| !part.is_empty()) {
+                    lines.push(vec![indent(depth), (Highlight::Text, part.to_string())]);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    lines
+}
+
+/// Elements starting on a new line in the rendered text.
+const BLOCK: [&str; 27] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+];
+
+/// Readable text of an html page: tags, scripts and styles removed, entities decoded.
+pub fn html_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut skip = 0usize;
+    let mut pre = 0usize;
+
+    for token in tokenize(html, true) {
+        match token {
+            Token::Open { name, closed, .. } => {
+                let name = name.to_ascii_lowercase();
+                if RAW_TEXT.contains(&name.as_str()) && !closed {
+                    skip += 1;
+                }
+                if name == "pre" {
+                    pre += 1;
+                }
+                if BLOCK.contains(&name.as_str()) {
+                    newline(&mut out);
+                }
+                if name == "li" {
+                    out.push_str("- ");
+                }
+            }
+            Token::Close(name) => {
+                let name = name.to_ascii_lowercase();
+                if RAW_TEXT.contains(&name.as_str()) {
+                    skip = skip.saturating_sub(1);
+                }
+                if name == "pre" {
+                    pre = pre.saturating_sub(1);
+                }
+                if BLOCK.contains(&name.as_str()) {
+                    newline(&mut out);
+                }
+            }
+            Token::Text(text) if skip == 0 => {
+                let text = entities(text);
+                match pre > 0 {
+                    true => out.push_str(&text),
+                    false => {
+                        let words: Vec<&str> = text.split_whitespace().collect();
+                        if text.starts_with(char::is_whitespace)
+                            && !out.ends_with(char::is_whitespace)
+                            && !out.is_empty()
+                        {
+                            out.push(' ');
+                        }
+                        out.push_str(&words.join(" "));
+                        if text.ends_with(char::is_whitespace) && !words.is_empty() {
+                            out.push(' ');
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // At most one empty line in a row.
+    let mut text = String::new();
+    let mut empty = 0;
+    for line in out.lines().map(str::trim_end) {
+        empty = match line.is_empty() {
+            true => empty + 1,
+            false => 0,
+        };
+        if empty < 2 {
+            text.extend(iter::once(line).chain(iter::once("\n")));
+        }
+    }
+    text.trim().to_string()
+}
+
+fn newline(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Decodes the common named and the numeric character references.
+fn entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "hellip" => Some('…'),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                _ => match entity.strip_prefix('#') {
+                    Some(code) => match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => code.parse().ok(),
+                    }
+                    .and_then(char::from_u32),
+                    None => None,
+                },
+            };
+            c.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The formatted lines without their highlighting.
+    fn lines(lines: Vec<StyledLine>) -> Vec<String> {
+        lines
+            .into_iter()
+            .map(|line| line.into_iter().map(|(_, text)| text).collect())
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_tags_text_and_self_closing_tags() {
+        assert_eq!(
+            tokenize("<a x=\"1\">hi<br/></a>", false),
+            [
+                Token::Open {
+                    name: "a",
+                    attributes: "x=\"1\"",
+                    closed: false
+                },
+                Token::Text("hi"),
+                Token::Open {
+                    name: "br",
+                    attributes: "",
+                    closed: true
+                },
+                Token::Close("a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_greater_than_signs_in_quoted_attributes() {
+        assert_eq!(
+            tokenize("<a title='1 > 0' href=\"x>y\">", false),
+            [Token::Open {
+                name: "a",
+                attributes: "title='1 > 0' href=\"x>y\"",
+                closed: false
+            }]
+        );
+    }
+
+    #[test]
+    fn tokenizes_comments_cdata_and_declarations() {
+        assert_eq!(
+            tokenize(
+                "<?xml version=\"1.0\"?><!-- a <b> --><![CDATA[<c>]]>",
+                false
+            ),
+            [
+                Token::Declaration("<?xml version=\"1.0\"?>"),
+                Token::Comment("<!-- a <b> -->"),
+                Token::Declaration("<![CDATA[<c>]]>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn takes_a_less_than_sign_not_starting_a_tag_as_text() {
+        assert_eq!(
+            tokenize("1 < 2", false),
+            [Token::Text("1 "), Token::Text("<"), Token::Text(" 2")]
+        );
+    }
+
+    #[test]
+    fn keeps_scripts_as_text_in_html() {
+        assert_eq!(
+            tokenize("<script>if (a<b) {}</script>", true),
+            [
+                Token::Open {
+                    name: "script",
+                    attributes: "",
+                    closed: false
+                },
+                Token::Text("if (a<b) {}"),
+                Token::Close("script"),
+            ]
+        );
+    }
+
+    #[test]
+    fn pretty_prints_xml() {
+        let body = "<?xml version=\"1.0\"?><root><item id=\"1\">one</item><empty/>\
+                    <!-- note --><list><a></a></list></root>";
+        assert_eq!(
+            lines(Format::Xml.apply(body)),
+            [
+                "<?xml version=\"1.0\"?>",
+                "<root>",
+                "  <item id=\"1\">one</item>",
+                "  <empty/>",
+                "  <!-- note -->",
+                "  <list>",
+                "    <a></a>",
+                "  </list>",
+                "</root>",
+            ]
+        );
+    }
+
+    #[test]
+    fn highlights_attributes_and_values() {
+        assert_eq!(
+            Format::Xml.apply("<a href='x'/>"),
+            [vec![
+                (Highlight::Text, String::new()),
+                (Highlight::Tag, String::from("<a")),
+                (Highlight::Attribute, String::from(" href")),
+                (Highlight::Tag, String::from("=")),
+                (Highlight::Value, String::from("'x'")),
+                (Highlight::Tag, String::from("/>")),
+            ]]
+        );
+    }
+
+    #[test]
+    fn does_not_indent_after_void_html_elements() {
+        assert_eq!(
+            lines(Format::Html.apply("<div><br><img src=\"a.png\"><p>text</p></div>")),
+            [
+                "<div>",
+                "  <br>",
+                "  <img src=\"a.png\">",
+                "  <p>text</p>",
+                "</div>",
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_the_text_of_html() {
+        let html =
+            "<html><head><style>p { color: red }</style><script>alert('<p>')</script></head>\
+                    <body><h1>Title</h1><p>Some   <b>bold</b>\n text.</p>\
+                    <ul><li>one</li><li>two</li></ul><pre>  kept\n    as is</pre></body></html>";
+        assert_eq!(
+            html_text(html),
+            "Title\nSome bold text.\n- one\n- two\n  kept\n    as is"
+        );
+    }
+
+    #[test]
+    fn decodes_named_and_numeric_entities() {
+        assert_eq!(
+            entities("&lt;a&gt; &amp; &quot;b&quot; &copy; &#65;&#x42;&#X43;"),
+            "<a> & \"b\" © ABC"
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_and_broken_entities_alone() {
+        assert_eq!(
+            entities("&unknown; & a;b &#xzz; &#1114112; &"),
+            "&unknown; & a;b &#xzz; &#1114112; &"
+        );
+        assert_eq!(html_text("<p>fish &amp; chips</p>"), "fish & chips");
+    }
+}
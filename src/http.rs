@@ -0,0 +1,494 @@
+use std::{
+    borrow::Cow,
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+    redirect::Policy,
+    Client, ClientBuilder, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::AppResult,
+    capture,
+    collection::{SavedRequest, Scripts},
+    content,
+    cookies::CookieJar,
+    download::{self, Body, Progress},
+    environment::{substitute, Environment, Variables},
+    proxy::ProxySettings,
+    script,
+    timing::{DnsTimer, Hop, Timing},
+    tls::{self, TlsSettings},
+};
+
+/// A response received for a sent request.
+#[derive(Debug, Clone, Default)]
+pub struct Response {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Text of the body preview, decoded with its charset.
+    pub body: String,
+    pub raw: Body,
+    /// Whether the body is not text.
+    pub binary: bool,
+    pub elapsed: Duration,
+    /// Proxy the request went through.
+    pub proxy: Option<String>,
+    pub timing: Timing,
+    /// Redirect responses received before this one, in order.
+    pub redirects: Vec<Hop>,
+}
+
+impl Response {
+    /// The whole decoded body, read back from the temporary file when only a preview is kept.
+    pub fn text(&self) -> Result<Cow<'_, str>, String> {
+        match &self.raw.file {
+            Some(file) => fs::read(file.as_ref())
+                .map(|bytes| Cow::Owned(content::decode(&self.headers, &bytes)))
+                .map_err(|err| format!("failed to read the whole body: {}", err)),
+            None => Ok(Cow::Borrowed(&self.body)),
+        }
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, strum::Display, strum::EnumIter, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpVersion {
+    #[default]
+    #[strum(serialize = "auto")]
+    Auto,
+    #[strum(serialize = "HTTP/1 only")]
+    Http1Only,
+    #[strum(serialize = "HTTP/2 prior knowledge")]
+    Http2PriorKnowledge,
+}
+
+/// How the http client sends requests. Timeouts of 0 mean no timeout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+    pub timeout_ms: u64,
+    pub connect_timeout_ms: u64,
+    pub follow_redirects: bool,
+    pub max_redirects: usize,
+    pub user_agent: String,
+    pub gzip: bool,
+    pub brotli: bool,
+    pub deflate: bool,
+    pub http_version: HttpVersion,
+    /// Bytes of a response body kept in memory, the rest is written to a temporary file.
+    pub preview_limit: u64,
+    pub proxy: ProxySettings,
+    /// Certificates by host pattern. The first matching entry is used.
+    pub tls: Vec<TlsSettings>,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 30_000,
+            connect_timeout_ms: 10_000,
+            follow_redirects: true,
+            max_redirects: 10,
+            user_agent: format!("neoman/{}", env!("CARGO_PKG_VERSION")),
+            gzip: true,
+            brotli: true,
+            deflate: true,
+            http_version: HttpVersion::Auto,
+            preview_limit: 1024 * 1024,
+            proxy: ProxySettings::default(),
+            tls: Vec::new(),
+        }
+    }
+}
+
+impl ClientSettings {
+    /// Uses the proxy of an environment and its TLS settings before the others.
+    pub fn apply_environment(&mut self, environment: &Environment) {
+        if let Some(proxy) = &environment.proxy {
+            self.proxy = proxy.clone();
+        }
+        self.tls.splice(0..0, environment.tls.iter().cloned());
+    }
+
+    fn builder(&self) -> AppResult<ClientBuilder> {
+        // Redirects are followed by `send` to record every hop.
+        let mut builder = Client::builder()
+            .user_agent(&self.user_agent)
+            .redirect(Policy::none())
+            .gzip(self.gzip)
+            .brotli(self.brotli)
+            .deflate(self.deflate);
+
+        if self.timeout_ms > 0 {
+            builder = builder.timeout(Duration::from_millis(self.timeout_ms));
+        }
+        if self.connect_timeout_ms > 0 {
+            builder = builder.connect_timeout(Duration::from_millis(self.connect_timeout_ms));
+        }
+
+        builder = match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1Only => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+
+        self.proxy.apply(builder)
+    }
+}
+
+/// A client with cookies and one without, for requests that have cookies disabled.
+#[derive(Debug, Clone)]
+struct Clients {
+    client: Client,
+    cookieless: Client,
+}
+
+impl Clients {
+    fn new(
+        settings: &ClientSettings,
+        tls: Option<&TlsSettings>,
+        cookies: &Arc<CookieJar>,
+    ) -> AppResult<Self> {
+        let builder = || -> AppResult<ClientBuilder> {
+            let builder = settings.builder()?.dns_resolver(Arc::new(DnsTimer));
+            match tls {
+                Some(tls) => tls.apply(builder),
+                None => Ok(builder),
+            }
+        };
+
+        Ok(Self {
+            client: builder()?.cookie_provider(cookies.clone()).build()?,
+            cookieless: builder()?.build()?,
+        })
+    }
+}
+
+/// Long-lived clients sharing connections and the cookie jar between requests.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    clients: Clients,
+    /// Clients for each entry of `settings.tls`, in the same order.
+    tls: Vec<Clients>,
+    pub cookies: Arc<CookieJar>,
+    pub settings: ClientSettings,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(Arc::default(), ClientSettings::default())
+            .expect("failed to build the http client")
+    }
+}
+
+impl HttpClient {
+    pub fn new(cookies: Arc<CookieJar>, settings: ClientSettings) -> AppResult<Self> {
+        Ok(Self {
+            clients: Clients::new(&settings, None, &cookies)?,
+            tls: settings
+                .tls
+                .iter()
+                .map(|tls| Clients::new(&settings, Some(tls), &cookies))
+                .collect::<AppResult<_>>()?,
+            cookies,
+            settings,
+        })
+    }
+
+    /// Rebuilds the clients with new settings, keeping the cookie jar.
+    pub fn with_settings(&self, settings: ClientSettings) -> AppResult<Self> {
+        Self::new(self.cookies.clone(), settings)
+    }
+
+    /// Rebuilds the clients around another cookie jar, keeping the settings.
+    pub fn with_cookies(&self, cookies: Arc<CookieJar>) -> AppResult<Self> {
+        Self::new(cookies, self.settings.clone())
+    }
+
+    /// TLS settings used for requests to `url`.
+    pub fn tls(&self, url: &Url) -> Option<&TlsSettings> {
+        self.settings.tls.iter().find(|tls| tls.matches(url))
+    }
+
+    /// The client to send `request` to `url` with.
+    pub fn client(&self, request: &SavedRequest, url: &Url) -> &Client {
+        let clients = self
+            .settings
+            .tls
+            .iter()
+            .position(|tls| tls.matches(url))
+            .and_then(|i| self.tls.get(i))
+            .unwrap_or(&self.clients);
+
+        match request.disable_cookies {
+            true => &clients.cookieless,
+            false => &clients.client,
+        }
+    }
+}
+
+/// Parses the url, assuming `https://` when the scheme is missing.
+pub fn parse_url(text: &str) -> AppResult<Url> {
+    match Url::parse(text) {
+        Ok(url) => Ok(url),
+        Err(_) => Ok(Url::parse(&format!("https://{}", text))?),
+    }
+}
+
+/// Builds a request with every `{{variable}}` substituted.
+pub fn build(request: &SavedRequest, variables: &Variables) -> AppResult<reqwest::Request> {
+    let url = parse_url(&substitute(&request.url, variables))?;
+    let mut req = reqwest::Request::new((&request.method).into(), url);
+
+    for header in &request.headers {
+        let name = HeaderName::from_bytes(substitute(&header.name, variables).as_bytes())?;
+        let value = HeaderValue::from_str(&substitute(&header.value, variables))?;
+        req.headers_mut().append(name, value);
+    }
+
+    if !request.body.is_empty() {
+        req.body_mut()
+            .replace(substitute(&request.body, variables).into());
+    }
+
+    Ok(req)
+}
+
+/// A curl command sending the request, with every `{{variable}}` substituted.
+pub fn curl(request: &SavedRequest, variables: &Variables) -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "'\\''"));
+
+    let mut command = String::from("curl");
+    let method = reqwest::Method::from(&request.method);
+    if method != reqwest::Method::GET {
+        command.push_str(&format!(" -X {}", method));
+    }
+    command.push_str(&format!(" {}", quote(&substitute(&request.url, variables))));
+
+    for header in &request.headers {
+        let header = format!(
+            "{}: {}",
+            substitute(&header.name, variables),
+            substitute(&header.value, variables)
+        );
+        command.push_str(&format!(" \\\n  -H {}", quote(&header)));
+    }
+    if !request.body.is_empty() {
+        let body = substitute(&request.body, variables);
+        command.push_str(&format!(" \\\n  --data-raw {}", quote(&body)));
+    }
+
+    command
+}
+
+fn headers(map: &HeaderMap) -> Vec<(String, String)> {
+    map.iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// The request following a redirect response, like a browser would send it.
+fn redirect(
+    req: &reqwest::Request,
+    status: StatusCode,
+    location: &str,
+) -> AppResult<reqwest::Request> {
+    let url = req.url().join(location)?;
+    let mut next = req.try_clone().ok_or("cannot resend a streamed body")?;
+
+    let to_get = matches!(
+        status,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER
+    ) && req.method() != reqwest::Method::HEAD
+        && (status == StatusCode::SEE_OTHER || req.method() == reqwest::Method::POST);
+    if to_get {
+        *next.method_mut() = reqwest::Method::GET;
+        *next.body_mut() = None;
+        for name in [
+            header::CONTENT_TYPE,
+            header::CONTENT_LENGTH,
+            header::CONTENT_ENCODING,
+        ] {
+            next.headers_mut().remove(name);
+        }
+    }
+
+    // Credentials are not sent to other hosts.
+    if url.host_str() != req.url().host_str()
+        || url.port_or_known_default() != req.url().port_or_known_default()
+    {
+        for name in [
+            header::AUTHORIZATION,
+            header::COOKIE,
+            header::PROXY_AUTHORIZATION,
+            header::WWW_AUTHENTICATE,
+        ] {
+            next.headers_mut().remove(name);
+        }
+    }
+
+    *next.url_mut() = url;
+    Ok(next)
+}
+
+/// Sends a request, following redirects as configured and timing every hop.
+pub async fn send(
+    client: &HttpClient,
+    request: &SavedRequest,
+    req: reqwest::Request,
+    progress: &Progress,
+) -> AppResult<Response> {
+    let start = Instant::now();
+    let mut redirects = Vec::new();
+    let mut req = req;
+
+    loop {
+        let method = req.method().to_string();
+        let url = req.url().to_string();
+        let next = req.try_clone();
+
+        let sent = Instant::now();
+        let (res, dns) = DnsTimer::time(client.client(request, req.url()).execute(req)).await;
+        let res = res.map_err(|err| tls::describe(&err))?;
+
+        let first_byte = sent.elapsed().saturating_sub(dns.unwrap_or_default());
+        let status = res.status();
+        let location = res
+            .headers()
+            .get(header::LOCATION)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+
+        if let (true, Some(location), Some(next)) = (status.is_redirection(), &location, &next) {
+            if client.settings.follow_redirects {
+                if redirects.len() >= client.settings.max_redirects {
+                    return Err(format!(
+                        "too many redirects, stopped after {}",
+                        client.settings.max_redirects
+                    )
+                    .into());
+                }
+
+                let following = redirect(next, status, location)?;
+                redirects.push(Hop {
+                    method,
+                    url,
+                    status: status.as_u16(),
+                    location: Some(location.clone()),
+                    headers: headers(res.headers()),
+                    timing: Timing {
+                        dns,
+                        first_byte,
+                        download: Duration::ZERO,
+                    },
+                });
+                req = following;
+                continue;
+            }
+        }
+
+        let headers = headers(res.headers());
+        let received = Instant::now();
+        let raw = download::download(res, client.settings.preview_limit, progress).await?;
+
+        return Ok(Response {
+            method,
+            url,
+            status: status.as_u16(),
+            body: content::decode(&headers, &raw.preview),
+            binary: content::is_binary(&headers, &raw.preview),
+            headers,
+            raw,
+            elapsed: start.elapsed(),
+            proxy: None,
+            timing: Timing {
+                dns,
+                first_byte,
+                download: received.elapsed(),
+            },
+            redirects,
+        });
+    }
+}
+
+/// Sends a request through the pre-request and post-response scripts of its
+/// folders and of itself, outermost first.
+///
+/// Captures are stored into `variables` when the response status is successful,
+/// before the post-response scripts run.
+///
+/// Returns `None` when a pre-request script skipped the request. Script output
+/// is appended to `logs` even when sending fails, and the names of the variables
+/// set by captures and scripts to `assigned`.
+pub async fn execute(
+    client: &HttpClient,
+    request: &SavedRequest,
+    folder_scripts: &[Scripts],
+    variables: &mut Variables,
+    assigned: &mut Vec<String>,
+    logs: &mut Vec<String>,
+    progress: &Progress,
+) -> AppResult<Option<Response>> {
+    let scripts: Vec<&Scripts> = folder_scripts
+        .iter()
+        .chain(std::iter::once(&request.scripts))
+        .collect();
+
+    let mut request = request.clone();
+    for pre_request in scripts
+        .iter()
+        .map(|s| &s.pre_request)
+        .filter(|s| !s.is_empty())
+    {
+        let output = script::pre_request(pre_request, &mut request, variables)
+            .map_err(|err| format!("pre-request script: {}", err))?;
+        logs.extend(output.logs);
+        assigned.extend(output.assigned);
+        if output.skip {
+            return Ok(None);
+        }
+    }
+
+    let req = build(&request, variables)?;
+    let proxy = client.settings.proxy.for_url(req.url());
+    let response = Response {
+        proxy,
+        ..send(client, &request, req, progress).await?
+    };
+
+    if response.status < 400 {
+        logs.extend(capture::apply(
+            &request.captures,
+            &response,
+            variables,
+            assigned,
+        ));
+    }
+
+    for post_response in scripts
+        .iter()
+        .map(|s| &s.post_response)
+        .filter(|s| !s.is_empty())
+    {
+        let output = script::post_response(post_response, &request, &response, variables)
+            .map_err(|err| format!("post-response script: {}", err))?;
+        logs.extend(output.logs);
+        assigned.extend(output.assigned);
+    }
+
+    Ok(Some(response))
+}
@@ -1,5 +1,6 @@
 use std::{
     cell::RefCell,
+    collections::HashSet,
     ops::{Deref, DerefMut},
     rc::Rc,
 };
@@ -10,6 +11,8 @@ pub struct ItemInner {
     pub name: String,
     pub selected: bool,
     pub active: bool,
+    /// Index path of the item in the collection.
+    pub path: Vec<usize>,
 }
 
 impl ItemInner {
@@ -18,6 +21,7 @@ impl ItemInner {
             name: name.to_string(),
             selected: false,
             active: false,
+            path: Vec::new(),
         }
     }
 }
@@ -56,6 +60,13 @@ impl Item {
     pub fn new(name: &str) -> Self {
         Item(Rc::new(RefCell::new(ItemInner::new(name))))
     }
+
+    pub fn with_path(name: &str, path: Vec<usize>) -> Self {
+        Item(Rc::new(RefCell::new(ItemInner {
+            path,
+            ..ItemInner::new(name)
+        })))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -116,4 +127,76 @@ impl<'a> StatefulTree<'a> {
             item.and_then(|item| item.children().get(i))
         })
     }
+
+    /// Selects the item shown on a row of the tree, counted from the first row in view.
+    ///
+    /// Returns false when the row is past the last item.
+    pub fn select_row(&mut self, row: usize) -> bool {
+        let opened = self.state.get_all_opened().into_iter().collect();
+        let mut rows = Vec::new();
+        shown(&self.items, &mut Vec::new(), &opened, &mut rows);
+        match rows.into_iter().nth(self.state.get_offset() + row) {
+            Some(path) => {
+                self.state.select(path);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Copies of the items for rendering, leaving out what closed items hold.
+    ///
+    /// The widget takes its items by value, and copying whole subtrees of a large JSON body
+    /// on every frame is slow. A closed item keeps one child so that it still shows as one
+    /// that opens.
+    pub fn visible_items(&self) -> Vec<TreeItem<'a, Item>> {
+        let opened = self.state.get_all_opened().into_iter().collect();
+        visible(&self.items, &mut Vec::new(), &opened)
+    }
+}
+
+/// Index paths of the items shown, in order, going into opened items.
+fn shown(
+    items: &[TreeItem<'_, Item>],
+    path: &mut Vec<usize>,
+    opened: &HashSet<Vec<usize>>,
+    rows: &mut Vec<Vec<usize>>,
+) {
+    for (i, item) in items.iter().enumerate() {
+        path.push(i);
+        rows.push(path.clone());
+        if opened.contains(path.as_slice()) {
+            shown(item.children(), path, opened, rows);
+        }
+        path.pop();
+    }
+}
+
+fn visible<'a>(
+    items: &[TreeItem<'a, Item>],
+    path: &mut Vec<usize>,
+    opened: &HashSet<Vec<usize>>,
+) -> Vec<TreeItem<'a, Item>> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            path.push(i);
+            let children = match opened.contains(path.as_slice()) {
+                true => visible(item.children(), path, opened),
+                false => item
+                    .children()
+                    .iter()
+                    .take(1)
+                    .map(|child| TreeItem::new_leaf(child.inner().clone()))
+                    .collect(),
+            };
+            path.pop();
+
+            match children.is_empty() {
+                true => TreeItem::new_leaf(item.inner().clone()),
+                false => TreeItem::new(item.inner().clone(), children),
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,45 @@
+use serde_json::Value;
+
+/// Selects the values matching a JSONPath expression.
+pub fn select(value: &Value, path: &str) -> Result<Vec<Value>, String> {
+    jsonpath_lib::select(value, path)
+        .map(|values| values.into_iter().cloned().collect())
+        .map_err(|err| format!("invalid JSONPath {}: {:?}", path, err))
+}
+
+/// Checks that `path` is a JSONPath expression, before there is a value to select from.
+pub fn check(path: &str) -> Result<(), String> {
+    jsonpath_lib::Compiled::compile(path)
+        .map(|_| ())
+        .map_err(|err| format!("invalid JSONPath {}: {}", path, err))
+}
+
+/// Parses `body` as JSON and selects the values matching `path`.
+pub fn select_str(body: &str, path: &str) -> Result<Vec<Value>, String> {
+    let value: Value =
+        serde_json::from_str(body).map_err(|err| format!("body is not JSON: {}", err))?;
+    select(&value, path)
+}
+
+/// Renders a value as plain text, without quotes around strings.
+pub fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Turns a jq-like path such as `.items[].name` into JSONPath, leaving JSONPath as it is.
+pub fn normalize(expression: &str) -> String {
+    let expression = expression.trim();
+    match expression.strip_prefix('.') {
+        Some(rest) if !expression.starts_with("..") => {
+            let rest = rest.replace("[]", "[*]");
+            match rest.starts_with('[') || rest.is_empty() {
+                true => format!("${}", rest),
+                false => format!("$.{}", rest),
+            }
+        }
+        _ => expression.to_string(),
+    }
+}
@@ -0,0 +1,889 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::vim;
+
+/// Something a key binding does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Save,
+    ToggleSidebar,
+    ToggleConsole,
+    OpenCookies,
+    OpenClientSettings,
+    OpenRunner,
+    /// Opens the focused text in `$VISUAL` or `$EDITOR`.
+    OpenEditor,
+    /// Opens the command palette.
+    OpenPalette,
+    ToggleHelp,
+    WidenSidebar,
+    NarrowSidebar,
+    /// Moves the border between the request and response panes down or right.
+    GrowRequest,
+    ShrinkRequest,
+    CycleOrientation,
+    /// Shows the focused pane alone, or all panes again.
+    ToggleMaximize,
+    NextPane,
+    PreviousPane,
+    Up,
+    Down,
+    Left,
+    Right,
+    First,
+    Last,
+    PageUp,
+    PageDown,
+    /// Opens, selects or toggles what is under the cursor.
+    Activate,
+    Cancel,
+    Insert,
+    /// Redoes an edit undone with `u` in the url bar or the request text.
+    Redo,
+    Send,
+    ToggleCookies,
+    PreviousTab,
+    NextTab,
+    SaveResponse,
+    CancelRequest,
+    CycleFormat,
+    Filter,
+    GotoOffset,
+    CopyValue,
+    CopyPath,
+    CopyUrl,
+    /// Copies the request as a curl command.
+    CopyCurl,
+    CopyBody,
+    /// Starts or stops selecting whole lines of the body.
+    SelectLines,
+    /// Copies the selected lines of the body.
+    CopyRegion,
+    /// Asks for the name of a response header to copy the value of.
+    CopyHeader,
+    /// Starts the run again from the first request.
+    StartRun,
+    /// Writes the summary of a finished run to `run-summary.json`.
+    ExportSummary,
+    MoreIterations,
+    FewerIterations,
+    LongerDelay,
+    ShorterDelay,
+    ToggleStopOnFailure,
+    AddCookie,
+    DeleteCookie,
+}
+
+impl Action {
+    /// The name of the action as a sentence, like `Toggle sidebar`.
+    pub fn label(&self) -> String {
+        let name = self.to_string().replace('_', " ");
+        format!("{}{}", name[..1].to_uppercase(), &name[1..])
+    }
+}
+
+/// Where a key binding applies, from the focused pane down to the whole application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum Context {
+    Global,
+    Sidebar,
+    Tabs,
+    MethodBar,
+    Urlbar,
+    RequestTab,
+    Requestbar,
+    Responsebar,
+    ResponseBody,
+    ResponseTree,
+    ResponseHex,
+    /// The popups, whose bindings do not fall back to `global`.
+    Runner,
+    Cookies,
+    ClientSettings,
+    Palette,
+    Help,
+}
+
+impl Context {
+    /// The context followed by those its bindings fall back to.
+    pub fn chain(self) -> Vec<Context> {
+        match self {
+            Context::Global => vec![Context::Global],
+            Context::ResponseBody | Context::ResponseTree | Context::ResponseHex => {
+                vec![self, Context::Responsebar, Context::Global]
+            }
+            Context::Runner
+            | Context::Cookies
+            | Context::ClientSettings
+            | Context::Palette
+            | Context::Help => vec![self],
+            _ => vec![self, Context::Global],
+        }
+    }
+}
+
+/// A key with its modifiers, like `ctrl-r`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for KeyChord {
+    /// Leaves out shift on characters and back tab, which is already in the key itself.
+    fn from(key_event: KeyEvent) -> Self {
+        let mut modifiers = key_event.modifiers & !KeyModifiers::SHIFT;
+        let code = match key_event.code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            KeyCode::Char(c) => KeyCode::Char(c),
+            KeyCode::BackTab => KeyCode::BackTab,
+            code => {
+                modifiers = key_event.modifiers;
+                code
+            }
+        };
+        KeyChord { code, modifiers }
+    }
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = s;
+        while key.chars().count() > 1 {
+            let Some((modifier, rest)) = key.split_once('-') else {
+                break;
+            };
+            modifiers |= match modifier {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{}` in `{}`", modifier, s)),
+            };
+            key = rest;
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match NAMED_KEYS.iter().find(|(name, _)| *name == key) {
+                Some((_, code)) => *code,
+                None => match key.strip_prefix('f').map(str::parse::<u8>) {
+                    Some(Ok(n)) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{}`", s)),
+                },
+            },
+        };
+
+        if let KeyCode::Char(_) | KeyCode::BackTab = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(KeyChord { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => match NAMED_KEYS.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// Parses a key sequence such as `ctrl-r`, `gg` or `g shift-up`.
+///
+/// Words are separated by spaces, and a word that is not a single key is read as a sequence of
+/// characters.
+pub fn parse_keys(s: &str) -> Result<Vec<KeyChord>, String> {
+    let mut keys = Vec::new();
+    for word in s.split_whitespace() {
+        match word.parse::<KeyChord>() {
+            Ok(key) => keys.push(key),
+            Err(_) if !word.contains('-') => keys.extend(word.chars().map(|c| KeyChord {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::NONE,
+            })),
+            Err(err) => return Err(err),
+        }
+    }
+    match keys.is_empty() {
+        true => Err(String::from("no keys given")),
+        false => Ok(keys),
+    }
+}
+
+/// Formats a key sequence the way it is written in the config file.
+pub fn format_keys(keys: &[KeyChord]) -> String {
+    let plain = keys
+        .iter()
+        .all(|key| key.modifiers.is_empty() && matches!(key.code, KeyCode::Char(c) if c != ' '));
+    let keys = keys.iter().map(|key| key.to_string());
+    match plain && keys.len() > 1 {
+        true => keys.collect(),
+        false => keys.collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// Keys of an action in the config file, one sequence or a list of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    pub fn sequences(&self) -> Vec<&str> {
+        match self {
+            Keys::One(keys) => vec![keys],
+            Keys::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Key bindings of the config file, by context name and then action name.
+pub type KeyConfig = BTreeMap<String, BTreeMap<String, Keys>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub context: Context,
+    pub keys: Vec<KeyChord>,
+    pub action: Action,
+}
+
+const DEFAULT_BINDINGS: &[(Context, Action, &[&str])] = &[
+    (Context::Global, Action::Quit, &["ctrl-c"]),
+    (Context::Global, Action::Save, &["ctrl-s"]),
+    (Context::Global, Action::ToggleSidebar, &["ctrl-b"]),
+    (Context::Global, Action::ToggleConsole, &["ctrl-l"]),
+    (Context::Global, Action::OpenCookies, &["ctrl-k"]),
+    (Context::Global, Action::OpenClientSettings, &["ctrl-o"]),
+    (Context::Global, Action::OpenRunner, &["alt-r"]),
+    (Context::Global, Action::OpenEditor, &["ctrl-e"]),
+    (Context::Global, Action::OpenPalette, &["ctrl-p"]),
+    (Context::Global, Action::ToggleHelp, &["?"]),
+    (
+        Context::Global,
+        Action::WidenSidebar,
+        &["alt-right", "alt-l"],
+    ),
+    (
+        Context::Global,
+        Action::NarrowSidebar,
+        &["alt-left", "alt-h"],
+    ),
+    (Context::Global, Action::GrowRequest, &["alt-down", "alt-j"]),
+    (Context::Global, Action::ShrinkRequest, &["alt-up", "alt-k"]),
+    (Context::Global, Action::CycleOrientation, &["alt-o"]),
+    (Context::Global, Action::ToggleMaximize, &["alt-z"]),
+    (Context::Global, Action::CopyUrl, &["alt-u"]),
+    (Context::Global, Action::CopyCurl, &["alt-y"]),
+    (Context::Global, Action::NextPane, &["tab", ".", "]"]),
+    (Context::Global, Action::PreviousPane, &[",", "["]),
+    (Context::Sidebar, Action::Activate, &["enter", "space", "o"]),
+    (Context::Sidebar, Action::Left, &["left"]),
+    (Context::Sidebar, Action::Right, &["right"]),
+    (Context::Sidebar, Action::Down, &["down"]),
+    (Context::Sidebar, Action::Up, &["up"]),
+    (Context::Sidebar, Action::First, &["home"]),
+    (Context::Sidebar, Action::Last, &["end"]),
+    (Context::Tabs, Action::Left, &["left", "up"]),
+    (Context::Tabs, Action::Right, &["right", "down"]),
+    (Context::Tabs, Action::First, &["home"]),
+    (Context::Tabs, Action::Last, &["end"]),
+    (Context::MethodBar, Action::Left, &["h", "left"]),
+    (Context::MethodBar, Action::Right, &["l", "right"]),
+    (Context::MethodBar, Action::Down, &["j", "down"]),
+    (Context::MethodBar, Action::Up, &["k", "up"]),
+    (Context::MethodBar, Action::Cancel, &["esc"]),
+    (Context::MethodBar, Action::Activate, &["enter"]),
+    (Context::Urlbar, Action::Insert, &["enter"]),
+    (Context::Urlbar, Action::Redo, &["ctrl-r"]),
+    (Context::Urlbar, Action::Send, &["o"]),
+    (
+        Context::RequestTab,
+        Action::Left,
+        &["h", "left", "j", "down"],
+    ),
+    (
+        Context::RequestTab,
+        Action::Right,
+        &["l", "right", "k", "up"],
+    ),
+    (Context::Requestbar, Action::Insert, &["enter"]),
+    (Context::Requestbar, Action::Redo, &["ctrl-r"]),
+    (Context::Requestbar, Action::ToggleCookies, &["alt-c"]),
+    (Context::Requestbar, Action::Send, &["o"]),
+    (Context::Responsebar, Action::PreviousTab, &["h", "left"]),
+    (Context::Responsebar, Action::NextTab, &["l", "right"]),
+    (Context::Responsebar, Action::SaveResponse, &["s"]),
+    (Context::Responsebar, Action::CancelRequest, &["x", "esc"]),
+    (Context::Responsebar, Action::CopyHeader, &["H"]),
+    (Context::ResponseBody, Action::Down, &["j", "down"]),
+    (Context::ResponseBody, Action::Up, &["k", "up"]),
+    (Context::ResponseBody, Action::PageDown, &["pagedown"]),
+    (Context::ResponseBody, Action::PageUp, &["pageup"]),
+    (Context::ResponseBody, Action::First, &["gg", "home"]),
+    (Context::ResponseBody, Action::Last, &["G", "end"]),
+    (Context::ResponseBody, Action::CycleFormat, &["f"]),
+    (Context::ResponseBody, Action::Filter, &["/"]),
+    (Context::ResponseBody, Action::SelectLines, &["v", "V"]),
+    (Context::ResponseBody, Action::CopyRegion, &["y"]),
+    (Context::ResponseBody, Action::CopyBody, &["Y"]),
+    (Context::ResponseTree, Action::Down, &["j", "down"]),
+    (Context::ResponseTree, Action::Up, &["k", "up"]),
+    (Context::ResponseTree, Action::Left, &["left"]),
+    (Context::ResponseTree, Action::Right, &["right"]),
+    (Context::ResponseTree, Action::First, &["gg", "home"]),
+    (Context::ResponseTree, Action::Last, &["G", "end"]),
+    (Context::ResponseTree, Action::Activate, &["enter", "space"]),
+    (Context::ResponseTree, Action::CopyValue, &["y"]),
+    (Context::ResponseTree, Action::CopyPath, &["Y"]),
+    (Context::ResponseHex, Action::Down, &["j", "down"]),
+    (Context::ResponseHex, Action::Up, &["k", "up"]),
+    (Context::ResponseHex, Action::PageDown, &["pagedown"]),
+    (Context::ResponseHex, Action::PageUp, &["pageup"]),
+    (Context::ResponseHex, Action::First, &["gg", "home"]),
+    (Context::ResponseHex, Action::Last, &["G", "end"]),
+    (Context::ResponseHex, Action::GotoOffset, &[":"]),
+    (Context::ResponseHex, Action::CopyBody, &["Y"]),
+    (Context::Runner, Action::Quit, &["ctrl-c"]),
+    (Context::Runner, Action::ToggleHelp, &["?"]),
+    (Context::Runner, Action::Cancel, &["esc", "q"]),
+    (Context::Runner, Action::Down, &["j", "down"]),
+    (Context::Runner, Action::Up, &["k", "up"]),
+    (Context::Runner, Action::StartRun, &["enter", "s"]),
+    (Context::Runner, Action::MoreIterations, &["+"]),
+    (Context::Runner, Action::FewerIterations, &["-"]),
+    (Context::Runner, Action::LongerDelay, &[">"]),
+    (Context::Runner, Action::ShorterDelay, &["<"]),
+    (Context::Runner, Action::ToggleStopOnFailure, &["b"]),
+    (Context::Runner, Action::ExportSummary, &["x"]),
+    (Context::Cookies, Action::Quit, &["ctrl-c"]),
+    (Context::Cookies, Action::ToggleHelp, &["?"]),
+    (Context::Cookies, Action::Cancel, &["esc", "q"]),
+    (Context::Cookies, Action::Down, &["j", "down"]),
+    (Context::Cookies, Action::Up, &["k", "up"]),
+    (Context::Cookies, Action::AddCookie, &["a"]),
+    (Context::Cookies, Action::Activate, &["e", "enter"]),
+    (Context::Cookies, Action::DeleteCookie, &["d"]),
+    (Context::ClientSettings, Action::Quit, &["ctrl-c"]),
+    (Context::ClientSettings, Action::ToggleHelp, &["?"]),
+    (Context::ClientSettings, Action::Cancel, &["esc", "q"]),
+    (Context::ClientSettings, Action::Down, &["j", "down"]),
+    (Context::ClientSettings, Action::Up, &["k", "up"]),
+    (
+        Context::ClientSettings,
+        Action::Activate,
+        &["enter", "space"],
+    ),
+    (Context::Palette, Action::Quit, &["ctrl-c"]),
+    (Context::Palette, Action::Cancel, &["esc"]),
+    (Context::Palette, Action::Down, &["down", "ctrl-n"]),
+    (Context::Palette, Action::Up, &["up", "ctrl-p"]),
+    (Context::Palette, Action::Activate, &["enter"]),
+    (Context::Help, Action::Quit, &["ctrl-c"]),
+    (Context::Help, Action::Cancel, &["esc", "q", "?"]),
+    (Context::Help, Action::Down, &["j", "down"]),
+    (Context::Help, Action::Up, &["k", "up"]),
+    (Context::Help, Action::PageDown, &["pagedown"]),
+    (Context::Help, Action::PageUp, &["pageup"]),
+    (Context::Help, Action::First, &["gg", "home"]),
+    (Context::Help, Action::Last, &["G", "end"]),
+];
+
+/// Key bindings, and the keys of a sequence typed so far.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    pub bindings: Vec<Binding>,
+    pub pending: Vec<KeyChord>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .flat_map(|(context, action, keys)| {
+                keys.iter().map(|keys| Binding {
+                    context: *context,
+                    keys: parse_keys(keys).expect("invalid default key binding"),
+                    action: *action,
+                })
+            })
+            .collect();
+
+        Keymap {
+            bindings,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Keymap {
+    /// The default bindings, with those of actions set in the config file replaced.
+    ///
+    /// Also returns the unknown contexts, actions and keys of the config file.
+    pub fn with_config(config: &KeyConfig) -> (Self, Vec<String>) {
+        let mut keymap = Keymap::default();
+        let mut problems = Vec::new();
+
+        for (context_name, actions) in config {
+            let Ok(context) = Context::from_str(context_name) else {
+                problems.push(format!("unknown context `{}`", context_name));
+                continue;
+            };
+            for (action_name, keys) in actions {
+                let Ok(action) = Action::from_str(action_name) else {
+                    problems.push(format!("unknown action `{}`", action_name));
+                    continue;
+                };
+
+                keymap
+                    .bindings
+                    .retain(|binding| binding.context != context || binding.action != action);
+                for keys in keys.sequences() {
+                    match parse_keys(keys) {
+                        Ok(keys) => keymap.bindings.push(Binding {
+                            context,
+                            keys,
+                            action,
+                        }),
+                        Err(err) => problems.push(format!("{}.{}: {}", context, action, err)),
+                    }
+                }
+            }
+        }
+
+        (keymap, problems)
+    }
+
+    /// Bindings that can never be reached, because another one takes the same keys or starts
+    /// with them, in the same context or one tried before, or vim editing takes their first key.
+    /// Global bindings are also reported where a pane binds their keys to something else.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for binding in &self.bindings {
+            let edited = matches!(
+                binding.context,
+                Context::Global | Context::Urlbar | Context::Requestbar
+            );
+            if edited && vim::takes(&binding.keys[0]) {
+                conflicts.push(format!(
+                    "`{}` ({} in {}) is taken by vim editing in the url bar and request text",
+                    format_keys(&binding.keys),
+                    binding.action,
+                    binding.context
+                ));
+            }
+        }
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in &self.bindings[i + 1..] {
+                let related = a.context.chain().contains(&b.context)
+                    || b.context.chain().contains(&a.context);
+                if !related || a.action == b.action && a.keys == b.keys {
+                    continue;
+                }
+
+                let (short, long) = match a.keys.len() <= b.keys.len() {
+                    true => (a, b),
+                    false => (b, a),
+                };
+                if a.keys == b.keys && a.context == b.context {
+                    conflicts.push(format!(
+                        "`{}` is bound to both {} and {} in {}",
+                        format_keys(&a.keys),
+                        a.action,
+                        b.action,
+                        a.context
+                    ));
+                } else if a.keys == b.keys {
+                    let (near, far) = match a.context.chain().contains(&b.context) {
+                        true => (a, b),
+                        false => (b, a),
+                    };
+                    // A pane may take over the keys of the pane around it, like the tree folding
+                    // with left and right, but not a global binding.
+                    if far.context != Context::Global {
+                        continue;
+                    }
+                    conflicts.push(format!(
+                        "`{}` ({} in {}) hides {} in {}",
+                        format_keys(&near.keys),
+                        near.action,
+                        near.context,
+                        far.action,
+                        far.context
+                    ));
+                } else if short.keys.len() < long.keys.len() && long.keys.starts_with(&short.keys) {
+                    conflicts.push(format!(
+                        "`{}` ({} in {}) hides `{}` ({} in {})",
+                        format_keys(&short.keys),
+                        short.action,
+                        short.context,
+                        format_keys(&long.keys),
+                        long.action,
+                        long.context
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Keys bound to an action in the first of `contexts` that binds it.
+    pub fn keys(&self, contexts: &[Context], action: Action) -> Vec<String> {
+        contexts
+            .iter()
+            .map(|context| {
+                self.bindings
+                    .iter()
+                    .filter(|binding| binding.context == *context && binding.action == action)
+                    .map(|binding| format_keys(&binding.keys))
+                    .collect::<Vec<_>>()
+            })
+            .find(|keys| !keys.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// The first key of each group of actions with what they do, like `enter: run  q: close`,
+    /// for the key hints of the popups.
+    pub fn hint(&self, context: Context, hints: &[(&[Action], &str)]) -> String {
+        hints
+            .iter()
+            .filter_map(|(actions, label)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.keys(&[context], *action).into_iter().next())
+                    .collect();
+                (!keys.is_empty()).then(|| format!("{}: {}", keys.join("/"), label))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// Actions bound in each of `contexts`, with their keys there, in the order they were bound.
+    pub fn help(&self, contexts: &[Context]) -> Vec<(Context, Vec<(String, Action)>)> {
+        contexts
+            .iter()
+            .map(|context| {
+                let mut actions = Vec::new();
+                for binding in self.bindings.iter().filter(|b| b.context == *context) {
+                    if !actions.contains(&binding.action) {
+                        actions.push(binding.action);
+                    }
+                }
+                let keys = actions
+                    .into_iter()
+                    .map(|action| (self.keys(&[*context], action).join(", "), action))
+                    .collect();
+                (*context, keys)
+            })
+            .collect()
+    }
+
+    /// Action of the key completing a sequence in the first of `contexts` that binds it.
+    ///
+    /// Keys starting a longer sequence are kept until it is complete. While text is being
+    /// typed only single keys with ctrl or alt are looked up.
+    pub fn lookup(
+        &mut self,
+        contexts: &[Context],
+        key_event: KeyEvent,
+        inserting: bool,
+    ) -> Option<Action> {
+        let key = KeyChord::from(key_event);
+        let bound = |binding: &&Binding| contexts.contains(&binding.context);
+
+        if inserting {
+            self.pending.clear();
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            {
+                return None;
+            }
+            return contexts.iter().find_map(|context| {
+                self.bindings
+                    .iter()
+                    .find(|binding| binding.context == *context && binding.keys == [key])
+                    .map(|binding| binding.action)
+            });
+        }
+
+        self.pending.push(key);
+        let action = contexts.iter().find_map(|context| {
+            self.bindings
+                .iter()
+                .find(|binding| binding.context == *context && binding.keys == self.pending)
+                .map(|binding| binding.action)
+        });
+        if action.is_some() {
+            self.pending.clear();
+            return action;
+        }
+
+        if self
+            .bindings
+            .iter()
+            .filter(bound)
+            .any(|binding| binding.keys.starts_with(&self.pending))
+        {
+            return None;
+        }
+
+        // The sequence led nowhere, the last key may still start another one.
+        let restart = self.pending.len() > 1;
+        self.pending.clear();
+        match restart {
+            true => self.lookup(contexts, key_event, inserting),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        KeyChord { code, modifiers }
+    }
+
+    fn press(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn config(context: &str, action: &str, keys: &str) -> KeyConfig {
+        let actions = BTreeMap::from([(action.to_string(), Keys::One(keys.to_string()))]);
+        BTreeMap::from([(context.to_string(), actions)])
+    }
+
+    #[test]
+    fn parses_key_chords() {
+        assert_eq!(
+            "ctrl-R".parse(),
+            Ok(key(KeyCode::Char('r'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            "shift-g".parse(),
+            Ok(key(KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            "alt-shift-up".parse(),
+            Ok(key(KeyCode::Up, KeyModifiers::ALT | KeyModifiers::SHIFT))
+        );
+        assert_eq!("-".parse(), Ok(key(KeyCode::Char('-'), KeyModifiers::NONE)));
+        assert_eq!(
+            "ctrl--".parse(),
+            Ok(key(KeyCode::Char('-'), KeyModifiers::CONTROL))
+        );
+        assert_eq!("f5".parse(), Ok(key(KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(
+            "space".parse(),
+            Ok(key(KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+        assert!("meta-x".parse::<KeyChord>().is_err());
+        assert!("enterr".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn formats_key_chords_as_parsed() {
+        for keys in ["ctrl-r", "alt-shift-up", "G", "space", "f12", "backtab"] {
+            assert_eq!(keys.parse::<KeyChord>().unwrap().to_string(), keys);
+        }
+    }
+
+    #[test]
+    fn leaves_shift_out_of_typed_characters() {
+        let typed = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(typed), "G".parse().unwrap());
+        let typed = KeyEvent::new(KeyCode::Char('R'), KeyModifiers::CONTROL);
+        assert_eq!(KeyChord::from(typed), "ctrl-r".parse().unwrap());
+        let typed = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(typed), "shift-up".parse().unwrap());
+    }
+
+    #[test]
+    fn parses_key_sequences() {
+        let g = key(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(parse_keys("gg"), Ok(vec![g, g]));
+        assert_eq!(
+            parse_keys("g shift-up"),
+            Ok(vec![g, key(KeyCode::Up, KeyModifiers::SHIFT)])
+        );
+        assert!(parse_keys("g ctrl-").is_err());
+        assert!(parse_keys("  ").is_err());
+        assert_eq!(format_keys(&parse_keys("gg").unwrap()), "gg");
+        assert_eq!(format_keys(&parse_keys("g ctrl-r").unwrap()), "g ctrl-r");
+    }
+
+    #[test]
+    fn looks_up_the_first_context_binding_a_key() {
+        let mut keymap = Keymap::default();
+        let contexts = Context::ResponseTree.chain();
+        assert_eq!(
+            keymap.lookup(&contexts, press('y'), false),
+            Some(Action::CopyValue)
+        );
+        assert_eq!(
+            keymap.lookup(&contexts, press('s'), false),
+            Some(Action::SaveResponse)
+        );
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.lookup(&contexts, ctrl_c, false), Some(Action::Quit));
+    }
+
+    #[test]
+    fn waits_for_the_rest_of_a_sequence() {
+        let mut keymap = Keymap::default();
+        let contexts = Context::ResponseBody.chain();
+        assert_eq!(keymap.lookup(&contexts, press('g'), false), None);
+        assert_eq!(keymap.pending, parse_keys("g").unwrap());
+        assert_eq!(
+            keymap.lookup(&contexts, press('g'), false),
+            Some(Action::First)
+        );
+        assert!(keymap.pending.is_empty());
+    }
+
+    #[test]
+    fn restarts_a_sequence_that_leads_nowhere() {
+        let mut keymap = Keymap::default();
+        let contexts = Context::ResponseBody.chain();
+        assert_eq!(keymap.lookup(&contexts, press('g'), false), None);
+        assert_eq!(
+            keymap.lookup(&contexts, press('j'), false),
+            Some(Action::Down)
+        );
+        assert!(keymap.pending.is_empty());
+    }
+
+    #[test]
+    fn only_looks_up_chords_while_inserting() {
+        let mut keymap = Keymap::default();
+        let contexts = Context::Urlbar.chain();
+        assert_eq!(keymap.lookup(&contexts, press('?'), true), None);
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.lookup(&contexts, ctrl_s, true), Some(Action::Save));
+    }
+
+    #[test]
+    fn replaces_the_keys_of_configured_actions() {
+        let (keymap, problems) = Keymap::with_config(&config("global", "quit", "ctrl-q"));
+        assert!(problems.is_empty());
+        assert_eq!(keymap.keys(&[Context::Global], Action::Quit), ["ctrl-q"]);
+
+        let (_, problems) = Keymap::with_config(&config("global", "explode", "ctrl-q"));
+        assert_eq!(problems, ["unknown action `explode`"]);
+    }
+
+    #[test]
+    fn default_bindings_do_not_conflict() {
+        assert_eq!(Keymap::default().conflicts(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn finds_conflicting_bindings() {
+        let (keymap, _) = Keymap::with_config(&config("response_body", "save_response", "g"));
+        assert_eq!(
+            keymap.conflicts(),
+            ["`g` (save_response in response_body) hides `gg` (first in response_body)"]
+        );
+
+        let (keymap, _) = Keymap::with_config(&config("response_tree", "copy_path", "y"));
+        assert_eq!(
+            keymap.conflicts(),
+            ["`y` is bound to both copy_value and copy_path in response_tree"]
+        );
+
+        let (keymap, _) = Keymap::with_config(&config("global", "toggle_help", "x"));
+        assert_eq!(
+            keymap.conflicts(),
+            [
+                "`x` (toggle_help in global) is taken by vim editing in the url bar and request text",
+                "`x` (cancel_request in responsebar) hides toggle_help in global",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_bindings_hidden_by_a_nearer_context() {
+        let (keymap, _) = Keymap::with_config(&config("global", "open_runner", "ctrl-r"));
+        assert_eq!(
+            keymap.conflicts(),
+            [
+                "`ctrl-r` (redo in urlbar) hides open_runner in global",
+                "`ctrl-r` (redo in requestbar) hides open_runner in global",
+            ]
+        );
+    }
+
+    #[test]
+    fn looks_up_popup_keys_without_global_ones() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.lookup(&Context::Runner.chain(), press('x'), false),
+            Some(Action::ExportSummary)
+        );
+        assert_eq!(
+            keymap.lookup(&Context::Cookies.chain(), press('1'), false),
+            None
+        );
+        assert_eq!(
+            keymap.hint(
+                Context::Cookies,
+                &[(&[Action::AddCookie], "add"), (&[Action::Cancel], "close")]
+            ),
+            "a: add  esc: close"
+        );
+    }
+}
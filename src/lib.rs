@@ -10,6 +10,78 @@ pub mod ui;
 /// Terminal user interface.
 pub mod tui;
 
+/// Mouse hit-testing.
+pub mod mouse;
+
+/// Key bindings.
+pub mod keymap;
+
+/// Vim-style text editing.
+pub mod vim;
+
+/// Editing texts in an external editor.
+pub mod editor;
+
+/// Colour themes.
+pub mod theme;
+
 pub mod items;
 
 pub mod component;
+
+/// Saved requests and folders.
+pub mod collection;
+
+/// Environments and variable substitution.
+pub mod environment;
+
+/// Proxy settings.
+pub mod proxy;
+
+/// Redirect hops and request timing.
+pub mod timing;
+
+/// Streamed response bodies.
+pub mod download;
+
+/// Content type detection and decoding.
+pub mod content;
+
+/// Response body formatting.
+pub mod format;
+
+/// Client certificates and certificate authorities.
+pub mod tls;
+
+/// Persistent cookie jar.
+pub mod cookies;
+
+/// User configuration file.
+pub mod config;
+
+/// System clipboard access.
+pub mod clipboard;
+
+/// Sending requests.
+pub mod http;
+
+/// Response assertions.
+pub mod assertion;
+
+/// Values captured from responses into variables.
+pub mod capture;
+
+/// JSONPath queries over response bodies.
+pub mod jsonpath;
+
+/// Pre-request and post-response scripts.
+pub mod script;
+
+/// Collection runner.
+pub mod runner;
+
+/// Run report writers.
+pub mod report;
+
+/// Command line interface.
+pub mod cli;
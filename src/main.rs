@@ -1,4 +1,9 @@
+use clap::Parser;
 use neoman::app::{App, AppResult};
+use neoman::cli::{self, Cli, Command};
+use neoman::collection::Collection;
+use neoman::config::Config;
+use neoman::editor;
 use neoman::event::{Event, EventHandler};
 use neoman::tui::Tui;
 use ratatui::backend::CrosstermBackend;
@@ -10,30 +15,57 @@ async fn main() -> AppResult<()> {
     let out = std::fs::File::create("output.log")?;
     tracing_subscriber::fmt().with_writer(out).init();
 
+    let args = Cli::parse();
+    if let Some(Command::Run(args)) = args.command {
+        let passed = cli::run(args).await?;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
     // Create an application.
-    let mut app = App::new();
+    let mut app = match args.collection {
+        Some(path) => App::with_collection(Collection::load(&path)?, path),
+        None => App::new(),
+    };
+    app.set_config(Config::load()?)?;
+    app.load_layout();
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stderr());
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(250);
+    app.waker = Some(events.sender());
     let mut tui = Tui::new(terminal, events);
     tui.init()?;
 
     // Start the main loop.
     while app.running {
+        // Hand the terminal to the external editor when a text is opened in it.
+        if let Some(edit) = app.editing.take() {
+            tui.suspend()?;
+            let result = editor::run(&edit);
+            tui.resume()?;
+            app.finish_edit(edit, result);
+        }
+
         // Render the user interface.
         tui.draw(&mut app)?;
         // Handle events.
         match tui.events.next()? {
-            Event::Tick => app.tick(),
-            Event::Key(key_event) => app.handle_key_events(key_event).await?,
-            Event::Mouse(_) => {}
+            Event::Tick => app.tick().await,
+            Event::Wake => app.poll().await,
+            Event::Key(key_event) => {
+                if let Err(err) = app.handle_key_events(key_event).await {
+                    app.notify_error(err.to_string());
+                }
+            }
+            Event::Mouse(mouse_event) => app.handle_mouse_events(mouse_event),
+            Event::Paste(text) => app.handle_paste(&text),
             Event::Resize(_, _) => {}
         }
     }
 
     // Exit the user interface.
     tui.exit()?;
+    app.save_layout();
     Ok(())
 }
@@ -0,0 +1,62 @@
+use ratatui::layout::Rect;
+
+/// Where the panes were last drawn, to find what the mouse points at.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Areas {
+    pub sidebar: Rect,
+    pub tabs: Rect,
+    pub method: Rect,
+    pub url: Rect,
+    pub request_tabs: Rect,
+    pub request: Rect,
+    pub response_tabs: Rect,
+    pub response: Rect,
+    /// Area shared by the request and response panes.
+    pub split: Rect,
+    /// Whether the request pane is above the response pane rather than beside it.
+    pub vertical: bool,
+}
+
+impl Areas {
+    /// Whether the position is on the border between the request and response panes.
+    pub fn on_split(&self, column: u16, row: u16) -> bool {
+        if !contains(self.split, column, row) || self.request.area() == 0 {
+            return false;
+        }
+        match self.vertical {
+            true => row + 1 == self.request.bottom(),
+            false => column + 1 == self.request.right(),
+        }
+    }
+
+    /// Percentage of the split given to the request pane when its border is dragged to the position.
+    pub fn split_at(&self, column: u16, row: u16) -> u16 {
+        let (offset, size) = match self.vertical {
+            true => (row.saturating_sub(self.split.y), self.split.height),
+            false => (column.saturating_sub(self.split.x), self.split.width),
+        };
+        (offset as u32 * 100 / size.max(1) as u32).clamp(10, 90) as u16
+    }
+}
+
+pub fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+}
+
+/// Index of the tab under `column` in a `Tabs` widget starting at `x`.
+///
+/// Each title is padded by a space on both sides and followed by a one column divider.
+pub fn tab_at<T: ToString>(titles: impl Iterator<Item = T>, x: u16, column: u16) -> Option<usize> {
+    let mut start = x;
+    for (i, title) in titles.enumerate() {
+        let end = start + title.to_string().chars().count() as u16 + 2;
+        if column < start {
+            return None;
+        }
+        if column < end {
+            return Some(i);
+        }
+        start = end + 1;
+    }
+    None
+}
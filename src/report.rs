@@ -0,0 +1,439 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use crate::{
+    app::AppResult,
+    runner::{RequestResult, RunSummary},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ReporterKind {
+    Cli,
+    Json,
+    Junit,
+}
+
+/// A report writer selected with `--reporter NAME[=FILE]`.
+#[derive(Debug, Clone)]
+pub struct Reporter {
+    pub kind: ReporterKind,
+    /// File to write the report to, standard output when missing.
+    pub output: Option<PathBuf>,
+}
+
+impl FromStr for Reporter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (kind, output) = match text.split_once('=') {
+            Some((kind, output)) => (kind, Some(PathBuf::from(output))),
+            None => (text, None),
+        };
+
+        Ok(Self {
+            kind: kind
+                .parse()
+                .map_err(|_| format!("unknown reporter {}, expected cli, json or junit", kind))?,
+            output,
+        })
+    }
+}
+
+impl Reporter {
+    pub fn write(&self, summary: &RunSummary) -> AppResult<()> {
+        match &self.output {
+            Some(path) => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                self.write_to(summary, &mut writer)?;
+                writer.flush()?;
+            }
+            None => self.write_to(summary, &mut io::stdout().lock())?,
+        }
+        Ok(())
+    }
+
+    fn write_to(&self, summary: &RunSummary, writer: &mut impl Write) -> AppResult<()> {
+        match self.kind {
+            ReporterKind::Cli => terminal(summary, writer)?,
+            ReporterKind::Json => {
+                serde_json::to_writer_pretty(&mut *writer, summary)?;
+                writeln!(writer)?;
+            }
+            ReporterKind::Junit => junit(summary, writer)?,
+        }
+        Ok(())
+    }
+}
+
+/// One line describing a request result, with failed assertions indented below.
+pub fn result_line(result: &RequestResult) -> String {
+    let status = match (result.status, &result.error) {
+        (_, Some(error)) => error.clone(),
+        (Some(status), None) => status.to_string(),
+        (None, None) => String::from("-"),
+    };
+
+    let mut line = format!(
+        "{} [{}] {}/{} {} {}ms",
+        match (result.skipped, result.passed) {
+            (true, _) => "SKIP",
+            (false, true) => "PASS",
+            (false, false) => "FAIL",
+        },
+        result.iteration,
+        result.folder,
+        result.name,
+        status,
+        result.elapsed_ms,
+    );
+
+    for log in &result.logs {
+        line.push_str(&format!("\n    > {}", log));
+    }
+
+    for assertion in result.assertions.iter().filter(|res| !res.passed) {
+        line.push_str(&format!(
+            "\n    {}: {}",
+            assertion.assertion,
+            assertion.message.clone().unwrap_or_default()
+        ));
+    }
+
+    line
+}
+
+/// Human readable summary, listing the failed requests again at the end.
+pub fn terminal(summary: &RunSummary, writer: &mut impl Write) -> io::Result<()> {
+    let failures: Vec<_> = summary.results.iter().filter(|res| res.failed()).collect();
+    if !failures.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "Failures:")?;
+        for result in failures {
+            writeln!(writer, "{}", result_line(result))?;
+        }
+    }
+
+    writeln!(writer)?;
+    writeln!(
+        writer,
+        "{} iteration(s), {} request(s): {} passed, {} failed, {} skipped",
+        summary.iterations, summary.total, summary.passed, summary.failed, summary.skipped
+    )?;
+    writeln!(
+        writer,
+        "total {}ms, average {}ms",
+        summary.total_ms, summary.average_ms
+    )
+}
+
+/// JUnit XML with one testsuite per folder and one testcase per request or assertion.
+pub fn junit(summary: &RunSummary, writer: &mut impl Write) -> io::Result<()> {
+    let mut folders: Vec<(&str, Vec<&RequestResult>)> = Vec::new();
+    for result in &summary.results {
+        match folders
+            .iter_mut()
+            .find(|(folder, _)| *folder == result.folder)
+        {
+            Some((_, results)) => results.push(result),
+            None => folders.push((&result.folder, vec![result])),
+        }
+    }
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<testsuites name="neoman" tests="{}" failures="{}" errors="{}" skipped="{}" time="{}">"#,
+        testcases(&summary.results),
+        failures(&summary.results),
+        errors(&summary.results),
+        skipped(&summary.results),
+        seconds(summary.total_ms),
+    )?;
+
+    for (folder, results) in folders {
+        writeln!(
+            writer,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" skipped="{}" time="{}">"#,
+            escape(folder),
+            testcases(results.iter().copied()),
+            failures(results.iter().copied()),
+            errors(results.iter().copied()),
+            skipped(results.iter().copied()),
+            seconds(results.iter().map(|res| res.elapsed_ms).sum()),
+        )?;
+
+        for result in results {
+            let name = match summary.iterations {
+                1 => result.name.clone(),
+                _ => format!("{} [{}]", result.name, result.iteration),
+            };
+            let attributes = format!(
+                r#"classname="{}" name="{}" time="{}""#,
+                escape(folder),
+                escape(&name),
+                seconds(result.elapsed_ms),
+            );
+
+            if result.skipped {
+                writeln!(writer, r#"    <testcase {}>"#, attributes)?;
+                writeln!(writer, "      <skipped/>")?;
+                writeln!(writer, "    </testcase>")?;
+            } else if let Some(error) = &result.error {
+                writeln!(writer, r#"    <testcase {}>"#, attributes)?;
+                writeln!(writer, r#"      <error message="{}"/>"#, escape(error))?;
+                writeln!(writer, "    </testcase>")?;
+            } else if result.assertions.is_empty() {
+                match result.passed {
+                    true => writeln!(writer, r#"    <testcase {}/>"#, attributes)?,
+                    false => {
+                        writeln!(writer, r#"    <testcase {}>"#, attributes)?;
+                        writeln!(
+                            writer,
+                            r#"      <failure message="unexpected status {}"/>"#,
+                            result.status.unwrap_or_default()
+                        )?;
+                        writeln!(writer, "    </testcase>")?;
+                    }
+                }
+            } else {
+                for assertion in &result.assertions {
+                    let attributes = format!(
+                        r#"classname="{}" name="{}: {}" time="{}""#,
+                        escape(folder),
+                        escape(&name),
+                        escape(&assertion.assertion),
+                        seconds(result.elapsed_ms),
+                    );
+                    match &assertion.message {
+                        None => writeln!(writer, r#"    <testcase {}/>"#, attributes)?,
+                        Some(message) => {
+                            writeln!(writer, r#"    <testcase {}>"#, attributes)?;
+                            writeln!(writer, r#"      <failure message="{}"/>"#, escape(message))?;
+                            writeln!(writer, "    </testcase>")?;
+                        }
+                    }
+                }
+            }
+        }
+
+        writeln!(writer, "  </testsuite>")?;
+    }
+
+    writeln!(writer, "</testsuites>")
+}
+
+fn testcases<'a>(results: impl IntoIterator<Item = &'a RequestResult>) -> usize {
+    results
+        .into_iter()
+        .map(
+            |res| match res.error.is_some() || res.assertions.is_empty() {
+                true => 1,
+                false => res.assertions.len(),
+            },
+        )
+        .sum()
+}
+
+fn failures<'a>(results: impl IntoIterator<Item = &'a RequestResult>) -> usize {
+    results
+        .into_iter()
+        .map(|res| match (&res.error, res.assertions.is_empty()) {
+            (Some(_), _) => 0,
+            (None, true) => usize::from(res.failed()),
+            (None, false) => res.assertions.iter().filter(|a| !a.passed).count(),
+        })
+        .sum()
+}
+
+/// Requests that failed without a response, written as `<error>` elements.
+fn errors<'a>(results: impl IntoIterator<Item = &'a RequestResult>) -> usize {
+    results
+        .into_iter()
+        .filter(|res| res.error.is_some())
+        .count()
+}
+
+fn skipped<'a>(results: impl IntoIterator<Item = &'a RequestResult>) -> usize {
+    results.into_iter().filter(|res| res.skipped).count()
+}
+
+fn seconds(ms: u128) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// Characters XML 1.0 does not allow, even as character references.
+fn invalid_in_xml(c: char) -> bool {
+    matches!(
+        c,
+        '\0'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}'
+    )
+}
+
+/// Escapes text for an attribute, dropping the control characters XML 1.0 does not allow.
+fn escape(text: &str) -> String {
+    text.replace(invalid_in_xml, "")
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assertion::AssertionResult;
+
+    fn result(folder: &str, name: &str) -> RequestResult {
+        RequestResult {
+            iteration: 1,
+            folder: folder.into(),
+            name: name.into(),
+            status: Some(200),
+            elapsed_ms: 250,
+            passed: true,
+            skipped: false,
+            error: None,
+            assertions: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    fn assertion(assertion: &str, message: Option<&str>) -> AssertionResult {
+        AssertionResult {
+            assertion: assertion.into(),
+            passed: message.is_none(),
+            message: message.map(String::from),
+        }
+    }
+
+    fn summary() -> RunSummary {
+        let results = vec![
+            RequestResult {
+                passed: false,
+                assertions: vec![
+                    assertion("status == 200", None),
+                    assertion(
+                        "jsonpath $.name == <a>",
+                        Some("expected $.name to be <a>, got \"b\" & 'c'\u{1b}[0m"),
+                    ),
+                ],
+                ..result("users", "get user")
+            },
+            RequestResult {
+                status: None,
+                passed: false,
+                error: Some(String::from("connection refused")),
+                ..result("users", "delete user")
+            },
+            RequestResult {
+                status: Some(500),
+                passed: false,
+                ..result("admin & co", "reset")
+            },
+            RequestResult {
+                status: None,
+                passed: false,
+                skipped: true,
+                ..result("admin & co", "audit")
+            },
+        ];
+        RunSummary {
+            iterations: 1,
+            total: 4,
+            passed: 0,
+            failed: 3,
+            skipped: 1,
+            total_ms: 1000,
+            average_ms: 250,
+            results,
+        }
+    }
+
+    #[test]
+    fn writes_junit_suites_per_folder() {
+        let mut out = Vec::new();
+        junit(&summary(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="neoman" tests="5" failures="2" errors="1" skipped="1" time="1.000">
+  <testsuite name="users" tests="3" failures="1" errors="1" skipped="0" time="0.500">
+    <testcase classname="users" name="get user: status == 200" time="0.250"/>
+    <testcase classname="users" name="get user: jsonpath $.name == &lt;a&gt;" time="0.250">
+      <failure message="expected $.name to be &lt;a&gt;, got &quot;b&quot; &amp; &apos;c&apos;[0m"/>
+    </testcase>
+    <testcase classname="users" name="delete user" time="0.250">
+      <error message="connection refused"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="admin &amp; co" tests="2" failures="1" errors="0" skipped="1" time="0.500">
+    <testcase classname="admin &amp; co" name="reset" time="0.250">
+      <failure message="unexpected status 500"/>
+    </testcase>
+    <testcase classname="admin &amp; co" name="audit" time="0.250">
+      <skipped/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[test]
+    fn numbers_the_iterations_of_junit_testcases() {
+        let mut summary = summary();
+        summary.iterations = 2;
+        summary.results.truncate(2);
+        summary.results[1].iteration = 2;
+
+        let mut out = Vec::new();
+        junit(&summary, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(r#"name="get user [1]: status == 200""#));
+        assert!(out.contains(r#"name="delete user [2]""#));
+    }
+
+    #[test]
+    fn drops_characters_xml_does_not_allow() {
+        assert_eq!(
+            escape("a\u{0}b\u{8}c\td\ne\rf\u{1f}g\u{fffe}h"),
+            "abc\td\ne\rfgh"
+        );
+        assert_eq!(escape("ünï €"), "ünï €");
+    }
+
+    #[test]
+    fn writes_the_summary_as_json() {
+        let reporter: Reporter = "json".parse().unwrap();
+        let mut out = Vec::new();
+        reporter.write_to(&summary(), &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(json["total"], 4);
+        assert_eq!(json["failed"], 3);
+        assert_eq!(json["skipped"], 1);
+        assert_eq!(json["results"][0]["assertions"][1]["passed"], false);
+        assert_eq!(json["results"][1]["error"], "connection refused");
+        assert_eq!(json["results"][3]["skipped"], true);
+        // Empty lists and missing errors are left out.
+        assert!(json["results"][2].get("assertions").is_none());
+        assert!(json["results"][2].get("error").is_none());
+    }
+
+    #[test]
+    fn parses_reporters_with_an_output_file() {
+        let reporter: Reporter = "junit=report.xml".parse().unwrap();
+        assert_eq!(reporter.kind, ReporterKind::Junit);
+        assert_eq!(reporter.output, Some(PathBuf::from("report.xml")));
+        assert_eq!(
+            "tap".parse::<Reporter>().unwrap_err(),
+            "unknown reporter tap, expected cli, json or junit"
+        );
+    }
+}
@@ -0,0 +1,588 @@
+use std::{
+    fs::File,
+    future::Future,
+    io::{BufReader, BufWriter},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    app::AppResult,
+    assertion::AssertionResult,
+    collection::{Collection, CollectionItem, SavedRequest, Scripts},
+    download::Progress,
+    environment::Variables,
+    http::{self, HttpClient, Response},
+};
+
+/// Outcome of a request of a run, with the variables its captures and scripts set, its script
+/// logs and its duration.
+pub type StepOutcome = (
+    Result<Option<Response>, String>,
+    Variables,
+    Vec<String>,
+    Duration,
+);
+
+/// A request queued in a run, along with the folder it belongs to.
+#[derive(Debug, Clone)]
+pub struct RunItem {
+    pub folder: String,
+    pub request: SavedRequest,
+    /// Scripts of the enclosing folders, outermost first.
+    pub scripts: Vec<Scripts>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    pub iterations: usize,
+    pub delay: Duration,
+    pub stop_on_failure: bool,
+    /// Variables bound for each iteration, cycled when there are fewer rows than iterations.
+    pub data: Vec<Variables>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 1,
+            delay: Duration::ZERO,
+            stop_on_failure: false,
+            data: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestResult {
+    pub iteration: usize,
+    pub folder: String,
+    pub name: String,
+    pub status: Option<u16>,
+    pub elapsed_ms: u128,
+    /// Never set on skipped requests, which neither pass nor fail.
+    pub passed: bool,
+    /// Set when a pre-request script skipped the request.
+    pub skipped: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<String>,
+}
+
+impl RequestResult {
+    fn new(
+        iteration: usize,
+        item: &RunItem,
+        elapsed: Duration,
+        response: AppResult<Option<Response>>,
+        logs: Vec<String>,
+    ) -> Self {
+        let skipped = matches!(response, Ok(None));
+        let (status, elapsed, error, assertions) = match response {
+            Ok(None) => (None, elapsed, None, Vec::new()),
+            Ok(Some(res)) => {
+                let assertions = item
+                    .request
+                    .assertions
+                    .iter()
+                    .map(|assertion| assertion.evaluate(&res))
+                    .collect();
+                (Some(res.status), res.elapsed, None, assertions)
+            }
+            Err(err) => (None, elapsed, Some(err.to_string()), Vec::new()),
+        };
+
+        // Without assertions, any non error status counts as a pass.
+        let passed = match (skipped, assertions.is_empty()) {
+            (true, _) => false,
+            (false, true) => status.is_some_and(|status| status < 400),
+            (false, false) => assertions.iter().all(|res: &AssertionResult| res.passed),
+        };
+
+        Self {
+            iteration,
+            folder: item.folder.clone(),
+            name: item.request.name.clone(),
+            status,
+            elapsed_ms: elapsed.as_millis(),
+            passed: error.is_none() && passed,
+            skipped,
+            error,
+            assertions,
+            logs,
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        !self.passed && !self.skipped
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub iterations: usize,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub total_ms: u128,
+    pub average_ms: u128,
+    pub results: Vec<RequestResult>,
+}
+
+impl RunSummary {
+    pub fn export(&self, path: &Path) -> AppResult<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Runs a list of requests in order, one request per [`Runner::step`], or per
+/// [`Runner::prepare`] and [`Runner::finish`] when the request is sent elsewhere.
+#[derive(Debug)]
+pub struct Runner {
+    pub items: Vec<RunItem>,
+    pub variables: Variables,
+    pub options: RunOptions,
+    pub results: Vec<RequestResult>,
+    client: HttpClient,
+    iteration: usize,
+    index: usize,
+    started: Option<Instant>,
+    finished: Option<Instant>,
+    next_at: Instant,
+}
+
+impl Runner {
+    pub fn new(
+        client: HttpClient,
+        items: Vec<RunItem>,
+        variables: Variables,
+        options: RunOptions,
+    ) -> Self {
+        Self {
+            items,
+            variables,
+            options,
+            results: Vec::new(),
+            client,
+            iteration: 0,
+            index: 0,
+            started: None,
+            finished: None,
+            next_at: Instant::now(),
+        }
+    }
+
+    pub fn start(&mut self) {
+        let now = Instant::now();
+
+        self.results.clear();
+        self.iteration = 0;
+        self.index = 0;
+        self.started = Some(now);
+        self.next_at = now;
+        self.finished = match self.total() {
+            0 => Some(now),
+            _ => None,
+        };
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started.is_some() && self.finished.is_none()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    pub fn total(&self) -> usize {
+        self.items.len() * self.options.iterations
+    }
+
+    /// Instant at which the next request is due, honouring the configured delay.
+    pub fn next_at(&self) -> Instant {
+        self.next_at
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match (self.started, self.finished) {
+            (Some(started), Some(finished)) => finished - started,
+            (Some(started), None) => started.elapsed(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Sends the next request if the run is in progress and the delay has passed.
+    pub async fn step(&mut self) -> Option<&RequestResult> {
+        let outcome = self.prepare()?.await;
+        Some(self.finish(outcome))
+    }
+
+    /// The next request if the run is in progress and the delay has passed, as a future that
+    /// does not borrow the runner so that it can be spawned. Its outcome goes to
+    /// [`Runner::finish`].
+    pub fn prepare(&self) -> Option<impl Future<Output = StepOutcome> + Send + 'static> {
+        if !self.is_running() || Instant::now() < self.next_at {
+            return None;
+        }
+
+        let client = self.client.clone();
+        let item = self.items[self.index].clone();
+        let mut variables = self.row_variables();
+
+        Some(async move {
+            let start = Instant::now();
+            let (mut assigned, mut logs) = (Vec::new(), Vec::new());
+            let response = http::execute(
+                &client,
+                &item.request,
+                &item.scripts,
+                &mut variables,
+                &mut assigned,
+                &mut logs,
+                &Progress::default(),
+            )
+            .await
+            .map_err(|err| err.to_string());
+            let set = assigned
+                .into_iter()
+                .filter_map(|name| {
+                    let value = variables.get(&name)?.clone();
+                    Some((name, value))
+                })
+                .collect();
+            (response, set, logs, start.elapsed())
+        })
+    }
+
+    /// Records the outcome of the request given by [`Runner::prepare`] and moves on.
+    pub fn finish(&mut self, (response, variables, logs, elapsed): StepOutcome) -> &RequestResult {
+        // Keep what scripts and captures set, but not the values of the data row.
+        self.variables.extend(variables);
+
+        let item = &self.items[self.index];
+        let response = response.map_err(Into::into);
+        let result = RequestResult::new(self.iteration + 1, item, elapsed, response, logs);
+        let failed = result.failed();
+        self.results.push(result);
+
+        self.index += 1;
+        if self.index == self.items.len() {
+            self.index = 0;
+            self.iteration += 1;
+        }
+
+        if (failed && self.options.stop_on_failure) || self.iteration == self.options.iterations {
+            self.finished = Some(Instant::now());
+        }
+        self.next_at = Instant::now() + self.options.delay;
+
+        &self.results[self.results.len() - 1]
+    }
+
+    /// Variables of the current iteration, with its data row bound over the run's own.
+    fn row_variables(&self) -> Variables {
+        let mut variables = self.variables.clone();
+        if !self.options.data.is_empty() {
+            let row = &self.options.data[self.iteration % self.options.data.len()];
+            variables.extend(row.clone());
+        }
+        variables
+    }
+
+    pub fn summary(&self) -> RunSummary {
+        let passed = self.results.iter().filter(|res| res.passed).count();
+        let total_ms = self.elapsed().as_millis();
+        let request_ms: u128 = self.results.iter().map(|res| res.elapsed_ms).sum();
+
+        RunSummary {
+            iterations: self.options.iterations,
+            total: self.results.len(),
+            passed,
+            failed: self.results.iter().filter(|res| res.failed()).count(),
+            skipped: self.results.iter().filter(|res| res.skipped).count(),
+            total_ms,
+            average_ms: request_ms / self.results.len().max(1) as u128,
+            results: self.results.clone(),
+        }
+    }
+}
+
+/// Collects the requests under the given index path, depth first.
+pub fn collect(collection: &Collection, path: &[usize]) -> Vec<RunItem> {
+    let mut folder = collection.name.clone();
+    let mut items = collection.items.as_slice();
+    let mut scripts = Vec::new();
+
+    for &i in path {
+        match items.get(i) {
+            Some(CollectionItem::Folder(child)) => {
+                folder = format!("{}/{}", folder, child.name);
+                scripts.push(child.scripts.clone());
+                items = &child.items;
+            }
+            Some(CollectionItem::Request(request)) => {
+                return vec![RunItem {
+                    folder,
+                    request: request.clone(),
+                    scripts,
+                }];
+            }
+            None => return Vec::new(),
+        }
+    }
+
+    let mut out = Vec::new();
+    flatten(items, &folder, &scripts, &mut out);
+    out
+}
+
+fn flatten(items: &[CollectionItem], folder: &str, scripts: &[Scripts], out: &mut Vec<RunItem>) {
+    for item in items {
+        match item {
+            CollectionItem::Folder(child) => flatten(
+                &child.items,
+                &format!("{}/{}", folder, child.name),
+                &[scripts, std::slice::from_ref(&child.scripts)].concat(),
+                out,
+            ),
+            CollectionItem::Request(request) => out.push(RunItem {
+                folder: folder.to_string(),
+                request: request.clone(),
+                scripts: scripts.to_vec(),
+            }),
+        }
+    }
+}
+
+/// Loads the rows of a CSV or JSON data file as variables.
+pub fn load_data(path: &Path) -> AppResult<Vec<Variables>> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => {
+            let mut reader = csv::Reader::from_path(path)?;
+            let headers = reader.headers()?.clone();
+
+            reader
+                .records()
+                .map(|record| {
+                    Ok(headers
+                        .iter()
+                        .zip(record?.iter())
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect())
+                })
+                .collect()
+        }
+        Some("json") => {
+            let rows: Vec<serde_json::Map<String, Value>> =
+                serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+            Ok(rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(name, value)| match value {
+                            Value::String(value) => (name, value),
+                            value => (name, value.to_string()),
+                        })
+                        .collect()
+                })
+                .collect())
+        }
+        _ => Err(format!("unsupported data file: {}", path.display()).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::collection::Folder;
+
+    fn request(name: &str) -> CollectionItem {
+        CollectionItem::Request(SavedRequest {
+            name: name.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn folder(name: &str, pre_request: &str, items: Vec<CollectionItem>) -> CollectionItem {
+        CollectionItem::Folder(Folder {
+            name: name.to_string(),
+            data: None,
+            scripts: Scripts {
+                pre_request: pre_request.to_string(),
+                ..Default::default()
+            },
+            items,
+        })
+    }
+
+    fn collection() -> Collection {
+        Collection {
+            name: String::from("api"),
+            environments: Vec::new(),
+            items: vec![
+                folder(
+                    "users",
+                    "a",
+                    vec![request("list"), folder("admin", "b", vec![request("ban")])],
+                ),
+                request("health"),
+            ],
+        }
+    }
+
+    fn names(items: &[RunItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| format!("{}/{}", item.folder, item.request.name))
+            .collect()
+    }
+
+    fn data_file(suffix: &str, content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    fn runner(items: usize, options: RunOptions) -> Runner {
+        let items = collect(&collection(), &[])
+            .into_iter()
+            .take(items)
+            .collect();
+        let mut runner = Runner::new(HttpClient::default(), items, Variables::new(), options);
+        runner.start();
+        runner
+    }
+
+    fn response(status: u16) -> Result<Option<Response>, String> {
+        Ok(Some(Response {
+            status,
+            ..Default::default()
+        }))
+    }
+
+    fn outcome(response: Result<Option<Response>, String>) -> StepOutcome {
+        (response, Variables::new(), Vec::new(), Duration::ZERO)
+    }
+
+    #[test]
+    fn collects_requests_depth_first() {
+        let items = collect(&collection(), &[]);
+        assert_eq!(
+            names(&items),
+            ["api/users/list", "api/users/admin/ban", "api/health"]
+        );
+        let scripts: Vec<_> = items[1].scripts.iter().map(|s| &s.pre_request).collect();
+        assert_eq!(scripts, ["a", "b"]);
+        assert!(items[2].scripts.is_empty());
+    }
+
+    #[test]
+    fn collects_the_requests_of_a_folder_or_a_single_request() {
+        assert_eq!(
+            names(&collect(&collection(), &[0])),
+            ["api/users/list", "api/users/admin/ban"]
+        );
+        let items = collect(&collection(), &[0, 1, 0]);
+        assert_eq!(names(&items), ["api/users/admin/ban"]);
+        assert_eq!(items[0].scripts.len(), 2);
+        assert!(collect(&collection(), &[5]).is_empty());
+    }
+
+    #[test]
+    fn loads_csv_rows() {
+        let file = data_file(".csv", "id,name\n1,ada\n2,\"grace, hopper\"\n");
+        let rows = load_data(file.path()).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["id"], "2");
+        assert_eq!(rows[1]["name"], "grace, hopper");
+    }
+
+    #[test]
+    fn loads_json_rows_as_text() {
+        let file = data_file(".json", r#"[{"id": 1, "name": "ada", "admin": true}]"#);
+        let rows = load_data(file.path()).unwrap();
+        assert_eq!(rows[0]["id"], "1");
+        assert_eq!(rows[0]["name"], "ada");
+        assert_eq!(rows[0]["admin"], "true");
+    }
+
+    #[test]
+    fn refuses_other_data_files() {
+        let file = data_file(".txt", "id\n1\n");
+        assert!(load_data(file.path()).is_err());
+        let file = data_file(".json", r#"{"id": 1}"#);
+        assert!(load_data(file.path()).is_err());
+    }
+
+    #[test]
+    fn counts_skipped_requests_apart() {
+        let mut runner = runner(
+            3,
+            RunOptions {
+                iterations: 2,
+                ..Default::default()
+            },
+        );
+        runner.finish(outcome(Ok(None)));
+        runner.finish(outcome(response(200)));
+        runner.finish(outcome(response(500)));
+        runner.finish(outcome(Err(String::from("connection refused"))));
+        runner.finish(outcome(Ok(None)));
+        runner.finish(outcome(response(204)));
+        assert!(runner.is_finished());
+
+        let summary = runner.summary();
+        assert_eq!(summary.total, 6);
+        assert_eq!(summary.passed, 2);
+        assert_eq!(summary.failed, 2);
+        assert_eq!(summary.skipped, 2);
+        assert!(!summary.results[0].passed && !summary.results[0].failed());
+    }
+
+    #[test]
+    fn stops_on_failure_but_not_on_skips() {
+        let mut runner = runner(
+            3,
+            RunOptions {
+                stop_on_failure: true,
+                ..Default::default()
+            },
+        );
+        runner.finish(outcome(Ok(None)));
+        assert!(runner.is_running());
+        runner.finish(outcome(response(404)));
+        assert!(runner.is_finished());
+        assert_eq!(runner.results.len(), 2);
+    }
+
+    #[test]
+    fn keeps_assigned_variables_but_not_the_data_row() {
+        let row = Variables::from([
+            (String::from("id"), String::from("1")),
+            (String::from("name"), String::from("ada")),
+        ]);
+        let mut runner = runner(
+            2,
+            RunOptions {
+                data: vec![row],
+                ..Default::default()
+            },
+        );
+        let assigned = Variables::from([(String::from("id"), String::from("1"))]);
+        runner.finish((response(200), assigned, Vec::new(), Duration::ZERO));
+        assert_eq!(runner.variables.get("id").map(String::as_str), Some("1"));
+        assert!(!runner.variables.contains_key("name"));
+    }
+}
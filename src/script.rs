@@ -0,0 +1,205 @@
+use std::{cell::RefCell, rc::Rc};
+
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use rhai::{Dynamic, Engine, Map, Scope};
+use sha2::{Digest, Sha256};
+use strum::IntoEnumIterator;
+
+use crate::{
+    collection::{Header, SavedRequest},
+    component::urlbar::Method,
+    environment::Variables,
+    http::Response,
+};
+
+/// State shared between a script and the functions it calls.
+#[derive(Debug, Default)]
+struct State {
+    variables: Variables,
+    assigned: Vec<String>,
+    logs: Vec<String>,
+    skip: bool,
+}
+
+/// What a script changed once it finished.
+#[derive(Debug, Default)]
+pub struct ScriptOutput {
+    pub logs: Vec<String>,
+    /// Names of the variables set with `set_var`, even to the value they had.
+    pub assigned: Vec<String>,
+    /// Set when a pre-request script called `skip()`.
+    pub skip: bool,
+}
+
+/// Sandboxed Rhai engine exposing the scripting API:
+///
+/// - `get_var(name)` and `set_var(name, value)` for environment variables
+/// - `log(value)` and `print(value)` to write to the console
+/// - `skip()` to skip the request from a pre-request script
+/// - `sha256(text)`, `hmac_sha256(key, text)` and `base64(text)` helpers
+fn engine(state: &Rc<RefCell<State>>) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_max_operations(1_000_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(10 * 1024 * 1024);
+    engine.disable_symbol("eval");
+
+    let console = state.clone();
+    engine.on_print(move |text| console.borrow_mut().logs.push(text.to_string()));
+    let console = state.clone();
+    engine.on_debug(move |text, _, _| console.borrow_mut().logs.push(text.to_string()));
+
+    let shared = state.clone();
+    engine.register_fn("log", move |value: Dynamic| {
+        shared.borrow_mut().logs.push(value.to_string())
+    });
+
+    let shared = state.clone();
+    engine.register_fn("get_var", move |name: &str| {
+        match shared.borrow().variables.get(name) {
+            Some(value) => Dynamic::from(value.clone()),
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let shared = state.clone();
+    engine.register_fn("set_var", move |name: &str, value: Dynamic| {
+        let mut state = shared.borrow_mut();
+        state.variables.insert(name.to_string(), value.to_string());
+        state.assigned.push(name.to_string());
+    });
+
+    let shared = state.clone();
+    engine.register_fn("skip", move || shared.borrow_mut().skip = true);
+
+    engine.register_fn("sha256", |text: &str| hex(&Sha256::digest(text.as_bytes())));
+    engine.register_fn("hmac_sha256", |key: &str, text: &str| {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("hmac accepts keys of any size");
+        mac.update(text.as_bytes());
+        hex(&mac.finalize().into_bytes())
+    });
+    engine.register_fn("base64", |text: &str| {
+        base64::engine::general_purpose::STANDARD.encode(text)
+    });
+
+    engine
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn run(script: &str, scope: &mut Scope, variables: &mut Variables) -> Result<ScriptOutput, String> {
+    let state = Rc::new(RefCell::new(State {
+        variables: variables.clone(),
+        ..Default::default()
+    }));
+
+    engine(&state)
+        .run_with_scope(scope, script)
+        .map_err(|err| err.to_string())?;
+
+    let state = state.take();
+    *variables = state.variables;
+
+    Ok(ScriptOutput {
+        logs: state.logs,
+        assigned: state.assigned,
+        skip: state.skip,
+    })
+}
+
+fn headers(headers: &[(String, String)]) -> Map {
+    headers
+        .iter()
+        .map(|(name, value)| (name.into(), Dynamic::from(value.clone())))
+        .collect()
+}
+
+/// Runs a pre-request script, which may modify `request` through the `request` map.
+pub fn pre_request(
+    script: &str,
+    request: &mut SavedRequest,
+    variables: &mut Variables,
+) -> Result<ScriptOutput, String> {
+    let mut map = Map::new();
+    map.insert("method".into(), request.method.to_string().into());
+    map.insert("url".into(), request.url.clone().into());
+    map.insert("body".into(), request.body.clone().into());
+    map.insert(
+        "headers".into(),
+        headers(
+            &request
+                .headers
+                .iter()
+                .map(|header| (header.name.clone(), header.value.clone()))
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+    );
+
+    let mut scope = Scope::new();
+    scope.push("request", map);
+
+    let output = run(script, &mut scope, variables)?;
+
+    let map: Map = scope.get_value("request").unwrap_or_default();
+    let text = |name: &str| map.get(name).map(|value| value.to_string());
+
+    if let Some(method) = text("method") {
+        request.method = Method::iter()
+            .find(|m| m.to_string().eq_ignore_ascii_case(&method))
+            .ok_or_else(|| format!("unknown method: {}", method))?;
+    }
+    if let Some(url) = text("url") {
+        request.url = url;
+    }
+    if let Some(body) = text("body") {
+        request.body = body;
+    }
+    if let Some(headers) = map
+        .get("headers")
+        .and_then(|value| value.read_lock::<Map>())
+    {
+        request.headers = headers
+            .iter()
+            .map(|(name, value)| Header {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+            .collect();
+    }
+
+    Ok(output)
+}
+
+/// Runs a post-response script, with the response available as the `response` map.
+pub fn post_response(
+    script: &str,
+    request: &SavedRequest,
+    response: &Response,
+    variables: &mut Variables,
+) -> Result<ScriptOutput, String> {
+    let body = response.text()?;
+    let json = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|json| rhai::serde::to_dynamic(json).ok())
+        .unwrap_or(Dynamic::UNIT);
+
+    let mut map = Map::new();
+    map.insert("status".into(), (response.status as i64).into());
+    map.insert("headers".into(), headers(&response.headers).into());
+    map.insert("body".into(), body.into_owned().into());
+    map.insert("time".into(), (response.elapsed.as_millis() as i64).into());
+    map.insert("json".into(), json);
+
+    let mut scope = Scope::new();
+    scope.push("response", map);
+    scope.push("request_name", request.name.clone());
+
+    run(script, &mut scope, variables)
+}
@@ -0,0 +1,449 @@
+use std::{collections::BTreeMap, env};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::{component::urlbar::Method, config::Config, format::Highlight};
+
+/// Styles of the user interface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub text: Style,
+    /// Panes without focus.
+    pub border: Style,
+    /// The focused pane and popups.
+    pub focus: Style,
+    /// Selected items, tabs and text.
+    pub highlight: Style,
+    /// Text inputs while typing.
+    pub insert: Style,
+    pub warning: Style,
+    pub pass: Style,
+    pub fail: Style,
+    pub get: Style,
+    pub post: Style,
+    pub put: Style,
+    pub patch: Style,
+    pub delete: Style,
+    pub head: Style,
+    pub options: Style,
+    pub informational: Style,
+    pub success: Style,
+    pub redirection: Style,
+    pub client_error: Style,
+    pub server_error: Style,
+    pub tag: Style,
+    pub attribute: Style,
+    pub value: Style,
+    pub comment: Style,
+    pub declaration: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// A theme of the config file, as styles like `"blue on gray bold"` replacing those of `base`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub styles: BTreeMap<String, String>,
+}
+
+const fn fg(color: Color) -> Style {
+    Style::new().fg(color)
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            text: fg(Color::White),
+            border: fg(Color::White),
+            focus: fg(Color::LightGreen),
+            highlight: fg(Color::LightBlue)
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            insert: fg(Color::LightYellow),
+            warning: fg(Color::Black).bg(Color::LightRed),
+            pass: fg(Color::LightGreen),
+            fail: fg(Color::LightRed),
+            get: fg(Color::LightGreen),
+            post: fg(Color::LightYellow),
+            put: fg(Color::LightBlue),
+            patch: fg(Color::LightMagenta),
+            delete: fg(Color::LightRed),
+            head: fg(Color::LightCyan),
+            options: fg(Color::Gray),
+            informational: fg(Color::LightCyan),
+            success: fg(Color::LightGreen),
+            redirection: fg(Color::LightYellow),
+            client_error: fg(Color::LightRed),
+            server_error: fg(Color::LightMagenta),
+            tag: fg(Color::LightBlue),
+            attribute: fg(Color::LightCyan),
+            value: fg(Color::LightGreen),
+            comment: fg(Color::DarkGray),
+            declaration: fg(Color::LightMagenta),
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            text: fg(Color::Black),
+            border: fg(Color::Black),
+            focus: fg(Color::Green),
+            highlight: fg(Color::Blue).bg(Color::Gray).add_modifier(Modifier::BOLD),
+            insert: fg(Color::Magenta),
+            warning: fg(Color::White).bg(Color::Red),
+            pass: fg(Color::Green),
+            fail: fg(Color::Red),
+            get: fg(Color::Green),
+            post: fg(Color::Yellow),
+            put: fg(Color::Blue),
+            patch: fg(Color::Magenta),
+            delete: fg(Color::Red),
+            head: fg(Color::Cyan),
+            options: fg(Color::DarkGray),
+            informational: fg(Color::Cyan),
+            success: fg(Color::Green),
+            redirection: fg(Color::Blue),
+            client_error: fg(Color::Red),
+            server_error: fg(Color::Magenta),
+            tag: fg(Color::Blue),
+            attribute: fg(Color::Cyan),
+            value: fg(Color::Green),
+            comment: fg(Color::DarkGray),
+            declaration: fg(Color::Magenta),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        let bold = |color| fg(color).add_modifier(Modifier::BOLD);
+        Theme {
+            text: fg(Color::White),
+            border: fg(Color::White),
+            focus: bold(Color::LightYellow),
+            highlight: bold(Color::Black).bg(Color::LightYellow),
+            insert: bold(Color::LightCyan),
+            warning: bold(Color::Black).bg(Color::LightRed),
+            pass: bold(Color::LightGreen),
+            fail: bold(Color::LightRed),
+            get: bold(Color::LightGreen),
+            post: bold(Color::LightYellow),
+            put: bold(Color::LightCyan),
+            patch: bold(Color::LightMagenta),
+            delete: bold(Color::LightRed),
+            head: bold(Color::White),
+            options: bold(Color::White),
+            informational: bold(Color::LightCyan),
+            success: bold(Color::LightGreen),
+            redirection: bold(Color::LightYellow),
+            client_error: bold(Color::LightRed),
+            server_error: bold(Color::LightMagenta),
+            tag: fg(Color::LightCyan),
+            attribute: fg(Color::White),
+            value: fg(Color::LightGreen),
+            comment: fg(Color::Gray),
+            declaration: fg(Color::LightMagenta),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// The theme chosen in the config file, dark by default, with its colours reduced to what the
+    /// terminal shows.
+    ///
+    /// Also returns the unknown themes, styles and colours of the config file.
+    pub fn with_config(config: &Config) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let name = config.theme.as_deref().unwrap_or("dark");
+
+        let theme = match (config.themes.get(name), Theme::builtin(name)) {
+            (Some(custom), _) => {
+                let base = custom.base.as_deref().unwrap_or("dark");
+                let mut theme = Theme::builtin(base).unwrap_or_else(|| {
+                    problems.push(format!("unknown base theme `{}`", base));
+                    Theme::dark()
+                });
+                for (field, style) in &custom.styles {
+                    if let Err(err) = theme.set(field, style) {
+                        problems.push(format!("{}.{}: {}", name, field, err));
+                    }
+                }
+                theme
+            }
+            (None, Some(theme)) => theme,
+            (None, None) => {
+                problems.push(format!("unknown theme `{}`", name));
+                Theme::dark()
+            }
+        };
+
+        (theme.fallback(ColorSupport::detect()), problems)
+    }
+
+    fn set(&mut self, field: &str, style: &str) -> Result<(), String> {
+        let style = parse_style(style)?;
+        let slot = match field {
+            "text" => &mut self.text,
+            "border" => &mut self.border,
+            "focus" => &mut self.focus,
+            "highlight" => &mut self.highlight,
+            "insert" => &mut self.insert,
+            "warning" => &mut self.warning,
+            "pass" => &mut self.pass,
+            "fail" => &mut self.fail,
+            "get" => &mut self.get,
+            "post" => &mut self.post,
+            "put" => &mut self.put,
+            "patch" => &mut self.patch,
+            "delete" => &mut self.delete,
+            "head" => &mut self.head,
+            "options" => &mut self.options,
+            "informational" => &mut self.informational,
+            "success" => &mut self.success,
+            "redirection" => &mut self.redirection,
+            "client_error" => &mut self.client_error,
+            "server_error" => &mut self.server_error,
+            "tag" => &mut self.tag,
+            "attribute" => &mut self.attribute,
+            "value" => &mut self.value,
+            "comment" => &mut self.comment,
+            "declaration" => &mut self.declaration,
+            _ => return Err(String::from("unknown style")),
+        };
+        *slot = style;
+        Ok(())
+    }
+
+    pub fn method(&self, method: &Method) -> Style {
+        match method {
+            Method::Get => self.get,
+            Method::Post => self.post,
+            Method::Put => self.put,
+            Method::Patch => self.patch,
+            Method::Delete => self.delete,
+            Method::Head => self.head,
+            Method::Options => self.options,
+        }
+    }
+
+    pub fn status(&self, status: u16) -> Style {
+        match status {
+            100..=199 => self.informational,
+            200..=299 => self.success,
+            300..=399 => self.redirection,
+            400..=499 => self.client_error,
+            _ => self.server_error,
+        }
+    }
+
+    pub fn syntax(&self, highlight: Highlight) -> Style {
+        match highlight {
+            Highlight::Text => self.text,
+            Highlight::Tag => self.tag,
+            Highlight::Attribute => self.attribute,
+            Highlight::Value => self.value,
+            Highlight::Comment => self.comment,
+            Highlight::Declaration => self.declaration,
+        }
+    }
+
+    /// The theme with the colours the terminal cannot show replaced by the closest of the 16
+    /// basic ones.
+    pub fn fallback(mut self, support: ColorSupport) -> Self {
+        for style in [
+            &mut self.text,
+            &mut self.border,
+            &mut self.focus,
+            &mut self.highlight,
+            &mut self.insert,
+            &mut self.warning,
+            &mut self.pass,
+            &mut self.fail,
+            &mut self.get,
+            &mut self.post,
+            &mut self.put,
+            &mut self.patch,
+            &mut self.delete,
+            &mut self.head,
+            &mut self.options,
+            &mut self.informational,
+            &mut self.success,
+            &mut self.redirection,
+            &mut self.client_error,
+            &mut self.server_error,
+            &mut self.tag,
+            &mut self.attribute,
+            &mut self.value,
+            &mut self.comment,
+            &mut self.declaration,
+        ] {
+            style.fg = style.fg.map(|color| support.reduce(color));
+            style.bg = style.bg.map(|color| support.reduce(color));
+        }
+        self
+    }
+}
+
+/// Colours a terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    Basic,
+    Indexed,
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Reads the support from `COLORTERM` and `TERM`.
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Indexed
+        } else {
+            ColorSupport::Basic
+        }
+    }
+
+    fn reduce(self, color: Color) -> Color {
+        let rgb = match (self, color) {
+            (ColorSupport::TrueColor, _) => return color,
+            (_, Color::Rgb(r, g, b)) => (r, g, b),
+            (ColorSupport::Basic, Color::Indexed(i)) if i >= 16 => indexed_rgb(i),
+            _ => return color,
+        };
+        BASIC
+            .iter()
+            .min_by_key(|(_, (r, g, b))| {
+                [(*r, rgb.0), (*g, rgb.1), (*b, rgb.2)]
+                    .iter()
+                    .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+                    .sum::<i32>()
+            })
+            .map(|(color, _)| *color)
+            .unwrap_or(color)
+    }
+}
+
+/// The 16 basic colours, with the values xterm gives them.
+const BASIC: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Value of one of the 240 colours after the basic ones of a 256 colour palette.
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        16..=231 => {
+            let level = |n: u8| match n {
+                0 => 0,
+                n => 55 + n * 40,
+            };
+            let i = i - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + (i - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Parses a style like `"blue"`, `"#268bd2 on black"` or `"black on 214 bold underlined"`.
+pub fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+    let mut words = s.split_whitespace().peekable();
+
+    if let Some(word) = words.next_if(|word| *word != "on" && parse_modifier(word).is_none()) {
+        style = style.fg(parse_color(word)?);
+    }
+    if words.next_if_eq(&"on").is_some() {
+        let word = words.next().ok_or("expected a colour after `on`")?;
+        style = style.bg(parse_color(word)?);
+    }
+    for word in words {
+        let modifier =
+            parse_modifier(word).ok_or_else(|| format!("unknown modifier `{}`", word))?;
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+fn parse_modifier(word: &str) -> Option<Modifier> {
+    match word {
+        "bold" => Some(Modifier::BOLD),
+        "dim" => Some(Modifier::DIM),
+        "italic" => Some(Modifier::ITALIC),
+        "underlined" => Some(Modifier::UNDERLINED),
+        "reversed" => Some(Modifier::REVERSED),
+        _ => None,
+    }
+}
+
+/// Parses a colour name, a `#rrggbb` value or an index in the 256 colour palette.
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    if let Some(hex) = s.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)
+            .ok_or_else(|| format!("invalid colour `{}`", s))?;
+        return Ok(Color::Rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ));
+    }
+    if let Ok(i) = s.parse::<u8>() {
+        return Ok(Color::Indexed(i));
+    }
+
+    let color = match s.replace(['-', '_'], "").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("unknown colour `{}`", s)),
+    };
+    Ok(color)
+}
@@ -0,0 +1,108 @@
+use std::{
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+
+/// Phases of a request, measured where possible.
+///
+/// Connecting and the TLS handshake happen inside the client, so they are part of
+/// `first_byte`. `dns` is only set when a new connection had to be opened.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timing {
+    pub dns: Option<Duration>,
+    /// From sending the request until the response headers arrived, after `dns`, including
+    /// connecting and the TLS handshake.
+    pub first_byte: Duration,
+    pub download: Duration,
+}
+
+impl Timing {
+    pub fn total(&self) -> Duration {
+        self.dns.unwrap_or_default() + self.first_byte + self.download
+    }
+}
+
+/// One response of a redirect chain.
+#[derive(Debug, Clone, Default)]
+pub struct Hop {
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub location: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub timing: Timing,
+}
+
+tokio::task_local! {
+    /// Where the resolver records its time, for the request sent in the current task.
+    static RESOLVING: Arc<Mutex<Option<Duration>>>;
+}
+
+/// Resolver recording how long resolving took for the request that needed it, so requests sent
+/// at the same time, like those of a run and of the user, don't take each other's time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DnsTimer;
+
+impl DnsTimer {
+    /// Runs `send`, with the time spent resolving a host name for it, if it had to.
+    pub async fn time<F: Future>(send: F) -> (F::Output, Option<Duration>) {
+        let resolving = Arc::new(Mutex::new(None));
+        let output = RESOLVING.scope(resolving.clone(), send).await;
+        let dns = resolving.lock().unwrap().take();
+        (output, dns)
+    }
+}
+
+impl Resolve for DnsTimer {
+    fn resolve(&self, name: Name) -> Resolving {
+        // The client asks for the address while polling the request, in its task.
+        let resolving = RESOLVING.try_with(Arc::clone).ok();
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(resolving) = resolving {
+                *resolving.lock().unwrap() = Some(start.elapsed());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    async fn resolve(host: &str) {
+        let mut addrs = DnsTimer
+            .resolve(Name::from_str(host).unwrap())
+            .await
+            .unwrap();
+        assert!(addrs.next().is_some());
+    }
+
+    #[tokio::test]
+    async fn times_the_resolution_of_each_request_apart() {
+        let ((), dns) = DnsTimer::time(resolve("localhost")).await;
+        assert!(dns.is_some());
+
+        let ((), dns) = DnsTimer::time(async {}).await;
+        assert_eq!(dns, None);
+
+        let (((), first), ((), second)) = tokio::join!(
+            DnsTimer::time(resolve("localhost")),
+            DnsTimer::time(async {})
+        );
+        assert!(first.is_some());
+        assert_eq!(second, None);
+    }
+}
@@ -0,0 +1,206 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p12_keystore::KeyStore;
+use reqwest::{Certificate, ClientBuilder, Identity, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::app::AppResult;
+
+/// TLS settings for the hosts matching `host`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// Host name, or a `*.domain` pattern. Empty or `*` matches every host.
+    pub host: String,
+    /// Client certificate, as PEM or as a `.p12`/`.pfx` PKCS#12 bundle.
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key, when it is not in the certificate file.
+    pub client_key: Option<PathBuf>,
+    /// Password of the PKCS#12 bundle.
+    pub password: Option<String>,
+    /// Extra root certificates, as PEM bundles or DER files.
+    pub ca_files: Vec<PathBuf>,
+    /// Accepts any server certificate. Only for testing.
+    pub skip_verify: bool,
+}
+
+impl TlsSettings {
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = url.host_str().unwrap_or_default();
+
+        match self.host.as_str() {
+            "" | "*" => true,
+            pattern => match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", domain.to_ascii_lowercase())),
+                None => host.eq_ignore_ascii_case(pattern),
+            },
+        }
+    }
+
+    /// Sets the certificates up on a client builder.
+    pub fn apply(&self, mut builder: ClientBuilder) -> AppResult<ClientBuilder> {
+        for path in &self.ca_files {
+            let data =
+                fs::read(path).map_err(|err| format!("CA file {}: {}", path.display(), err))?;
+            let certs = match data.starts_with(b"-----") {
+                true => Certificate::from_pem_bundle(&data)?,
+                false => vec![Certificate::from_der(&data)?],
+            };
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        if let Some(identity) = self.identity()? {
+            builder = builder.identity(identity);
+        }
+
+        Ok(builder.danger_accept_invalid_certs(self.skip_verify))
+    }
+
+    fn identity(&self) -> AppResult<Option<Identity>> {
+        let Some(path) = &self.client_cert else {
+            return Ok(None);
+        };
+        let data = fs::read(path)
+            .map_err(|err| format!("client certificate {}: {}", path.display(), err))?;
+
+        let pkcs12 = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("p12") || ext.eq_ignore_ascii_case("pfx"));
+
+        let mut pem = match pkcs12 {
+            true => pkcs12_to_pem(&data, self.password.as_deref().unwrap_or_default())?,
+            false => data,
+        };
+        if let Some(key) = &self.client_key {
+            pem.push(b'\n');
+            pem.extend(
+                fs::read(key).map_err(|err| format!("client key {}: {}", key.display(), err))?,
+            );
+        }
+
+        Ok(Some(Identity::from_pem(&pem)?))
+    }
+}
+
+/// Converts the key and certificate chain of a PKCS#12 bundle to PEM, which rustls reads.
+fn pkcs12_to_pem(data: &[u8], password: &str) -> AppResult<Vec<u8>> {
+    let store =
+        KeyStore::from_pkcs12(data, password).map_err(|err| format!("PKCS#12 bundle: {}", err))?;
+    let (_, chain) = store
+        .private_key_chain()
+        .ok_or("PKCS#12 bundle has no private key")?;
+
+    let block = |label: &str, der: &[u8]| {
+        let body = STANDARD.encode(der);
+        let lines: Vec<&str> = body
+            .as_bytes()
+            .chunks(64)
+            .map(|line| std::str::from_utf8(line).unwrap_or_default())
+            .collect();
+        format!(
+            "-----BEGIN {label}-----\n{}\n-----END {label}-----\n",
+            lines.join("\n")
+        )
+    };
+
+    let mut pem = block("PRIVATE KEY", chain.key());
+    for cert in chain.chain() {
+        pem.push_str(&block("CERTIFICATE", cert.as_der()));
+    }
+    Ok(pem.into_bytes())
+}
+
+/// Describes a failed request, with a hint when the TLS handshake failed.
+pub fn describe(err: &reqwest::Error) -> String {
+    let mut text = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        let message = err.to_string();
+        if !text.contains(&message) {
+            text.push_str(": ");
+            text.push_str(&message);
+        }
+        source = err.source();
+    }
+
+    let hints = [
+        (
+            "UnknownIssuer",
+            "the server certificate is signed by an unknown CA, add the CA to `ca_files`",
+        ),
+        (
+            "NotValidForName",
+            "the server certificate is not valid for this host name",
+        ),
+        ("Expired", "the server certificate has expired"),
+        ("NotValidYet", "the server certificate is not valid yet"),
+        (
+            "CertificateRequired",
+            "the server requires a client certificate, set `client_cert`",
+        ),
+        (
+            "BadCertificate",
+            "the server rejected the client certificate",
+        ),
+        (
+            "HandshakeFailure",
+            "the handshake failed, the server may require a client certificate or other ciphers",
+        ),
+        (
+            "InvalidContentType",
+            "the server did not answer with TLS, try `http://`",
+        ),
+        (
+            "received corrupt message",
+            "the server did not answer with TLS, try `http://`",
+        ),
+        (
+            "invalid peer certificate",
+            "the server certificate was rejected, `skip_verify` turns verification off",
+        ),
+    ];
+
+    match hints.iter().find(|(needle, _)| text.contains(needle)) {
+        Some((_, hint)) => format!("{}\n\nTLS error: {}", text, hint),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, url: &str) -> bool {
+        let settings = TlsSettings {
+            host: pattern.to_string(),
+            ..Default::default()
+        };
+        settings.matches(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn matches_every_host_without_a_pattern() {
+        assert!(matches("", "https://example.com"));
+        assert!(matches("*", "https://api.example.com/users"));
+    }
+
+    #[test]
+    fn matches_hosts_whatever_their_case() {
+        assert!(matches("api.example.com", "https://API.Example.com"));
+        assert!(matches("API.EXAMPLE.COM", "https://api.example.com:8443/x"));
+        assert!(!matches("api.example.com", "https://example.com"));
+    }
+
+    #[test]
+    fn matches_subdomains_of_a_wildcard() {
+        assert!(matches("*.example.com", "https://api.example.com"));
+        assert!(matches("*.Example.COM", "https://a.b.EXAMPLE.com"));
+        assert!(!matches("*.example.com", "https://example.com"));
+        assert!(!matches("*.example.com", "https://badexample.com"));
+    }
+}
@@ -1,6 +1,10 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, BorderType, Borders, Paragraph, Tabs, Wrap},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Cell, Clear, Gauge, Paragraph, Row, Table, TableState, Tabs,
+        Wrap,
+    },
 };
 use strum::IntoEnumIterator;
 use tui_menu::Menu;
@@ -8,35 +12,194 @@ use tui_tree_widget::Tree;
 
 use crate::{
     app::{App, Selected},
-    component::{requestbar::RequestMenu, urlbar::InputMode},
+    component::{
+        clientbar::ClientField,
+        requestbar::RequestMenu,
+        responsebar::{ResponseMenu, HEX_WIDTH},
+        urlbar::InputMode,
+    },
+    config::Orientation,
+    content, download,
+    keymap::{self, Action, Context},
+    mouse::Areas,
+    theme::Theme,
+    timing::Hop,
+    vim::Vim,
 };
 
-pub const HIGHLIGHT_STYLE: Style = Style::new()
-    .fg(Color::LightBlue)
-    .add_modifier(Modifier::BOLD)
-    .bg(Color::DarkGray);
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
+    app.areas = Areas::default();
 
-pub const SELECTED_STYLE: Style = Style::new().fg(Color::LightGreen);
+    let console_height = match app.settings.show_console {
+        true => 8,
+        false => 0,
+    };
 
-pub const INSERT_STYLE: Style = Style::new().fg(Color::LightYellow);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(console_height),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(frame.size());
 
-pub const DEFAULT_STYLE: Style = Style::new().fg(Color::White);
+    match (app.maximized(), &app.selected) {
+        (true, Selected::Sidebar) => sidebar(app, frame, chunks[0]),
+        (true, Selected::RequestTab | Selected::Requestbar) => requestbar(app, frame, chunks[0]),
+        (true, _) => responsebar(app, frame, chunks[0]),
+        (false, _) => {
+            let main_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(app.sidebar_size()), Constraint::Min(0)].as_ref())
+                .split(chunks[0]);
+
+            if app.settings.show_sidebar {
+                sidebar(app, frame, main_chunks[0]);
+            }
+            mainbar(app, frame, main_chunks[1]);
+        }
+    }
+
+    if app.settings.show_console {
+        console(app, frame, chunks[1]);
+    }
+    statusline(app, frame, chunks[2]);
+
+    if app.runner.is_some() {
+        runner(app, frame, centered(frame.size(), 80, 80));
+    }
+
+    if app.cookies.is_some() {
+        cookies(app, frame, centered(frame.size(), 80, 80));
+    }
+
+    if app.client_settings.is_some() {
+        client_settings(app, frame, centered(frame.size(), 60, 60));
+    }
+
+    if app.palette.is_some() {
+        palette(app, frame, centered(frame.size(), 60, 60));
+    }
+
+    if app.settings.show_help {
+        help(app, frame, centered(frame.size(), 60, 80));
+    }
+}
+
+/// Editing mode, focused pane, environment, open request and the keys of a command typed so far,
+/// with a notification or the last response on the right.
+pub fn statusline<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let (mode, vim) = match app.selected {
+        Selected::Urlbar => (app.urlbar.input_mode, Some(&app.urlbar.vim)),
+        Selected::Requestbar if app.requestbar.text().is_some() => {
+            (app.requestbar.input_mode, Some(&app.requestbar.vim))
+        }
+        _ if app.is_inserting() => (InputMode::Insert, None),
+        _ => (InputMode::Normal, None),
+    };
+
+    let (name, style) = match mode {
+        InputMode::Normal => (" NORMAL ", theme.focus),
+        InputMode::Insert => (" INSERT ", theme.insert),
+        InputMode::Visual => (" VISUAL ", theme.highlight),
+    };
+    let mut left = vec![
+        Span::styled(name, style.add_modifier(Modifier::REVERSED)),
+        Span::styled(format!(" {}", app.selected), theme.focus),
+        Span::raw(format!(" | {}", app.environment_name())),
+    ];
+    if let Some(path) = app
+        .collection
+        .path_name(&app.tab_path())
+        .filter(|path| !path.is_empty())
+    {
+        left.push(Span::raw(format!(" | {}", path)));
+    }
+
+    let pending = format!(
+        "{}{}",
+        vim.map(Vim::pending).unwrap_or_default(),
+        keymap::format_keys(&app.keymap.pending)
+    );
+    if !pending.is_empty() {
+        left.push(Span::styled(format!(" {}", pending), theme.insert));
+    }
+
+    let right = match (&app.notification, &app.pending, &app.responsebar.response) {
+        (Some(notification), _, _) => vec![Span::styled(
+            notification.message.clone(),
+            match notification.error {
+                true => theme.warning,
+                false => theme.focus,
+            },
+        )],
+        (None, Some(pending), _) => vec![Span::raw(format!(
+            "sending {}ms",
+            pending.started.elapsed().as_millis()
+        ))],
+        (None, None, Some(res)) => vec![
+            Span::styled(res.status.to_string(), theme.status(res.status)),
+            Span::raw(format!(" {}ms", res.elapsed.as_millis())),
+        ],
+        (None, None, None) => Vec::new(),
+    };
+    let width = right.iter().map(Span::width).sum::<usize>() as u16;
 
-/// Renders the user interface widgets.
-pub fn render<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(0)].as_ref())
-        .split(frame.size());
+        .constraints([Constraint::Min(0), Constraint::Length(width + 1)].as_ref())
+        .split(area);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(left)).style(theme.text),
+        chunks[0],
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(right))
+            .style(theme.text)
+            .alignment(Alignment::Right),
+        chunks[1],
+    );
+}
+
+/// Returns a rectangle of the given percentage size centered in `area`.
+pub fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
 
-    // sidebar(app, frame, chunks[0]);
-    mainbar(app, frame, chunks[0]);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
 }
 
 pub fn sidebar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
     let (style, highlight_style) = match app.selected == Selected::Sidebar {
-        true => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-        false => (DEFAULT_STYLE, DEFAULT_STYLE),
+        true => (theme.focus, theme.highlight),
+        false => (theme.border, theme.text),
     };
 
     let block = Block::default()
@@ -47,36 +210,54 @@ pub fn sidebar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect)
     let items = Tree::new(app.sidebar.tree.items.clone())
         .block(block)
         .highlight_style(highlight_style)
-        .style(DEFAULT_STYLE);
+        .style(theme.text);
 
     frame.render_stateful_widget(items, area, &mut app.sidebar.tree.state);
+    app.areas.sidebar = area;
 }
 
 pub fn mainbar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Min(7)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(7),
+            ]
+            .as_ref(),
+        )
         .split(area);
 
-    let direction = match chunks[1].height > 25 {
-        true => Direction::Vertical,
-        false => Direction::Horizontal,
+    let direction = match app.settings.orientation {
+        Orientation::Auto if chunks[2].height > 25 => Direction::Vertical,
+        Orientation::Auto | Orientation::Horizontal => Direction::Horizontal,
+        Orientation::Vertical => Direction::Vertical,
     };
     let lower_chunks = Layout::default()
         .direction(direction)
-        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
-        .split(chunks[1]);
+        .constraints(
+            [
+                Constraint::Percentage(app.settings.split),
+                Constraint::Percentage(100 - app.settings.split),
+            ]
+            .as_ref(),
+        )
+        .split(chunks[2]);
+    app.areas.split = chunks[2];
+    app.areas.vertical = direction == Direction::Vertical;
 
-    // tabs(app, frame, chunks[0]);
+    tabs(app, frame, chunks[0]);
     requestbar(app, frame, lower_chunks[0]);
     responsebar(app, frame, lower_chunks[1]);
-    urlbar(app, frame, chunks[0]);
+    urlbar(app, frame, chunks[1]);
 }
 
 pub fn tabs<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
     let (style, highlight_style) = match app.selected == Selected::Tabs {
-        true => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-        false => (DEFAULT_STYLE, DEFAULT_STYLE),
+        true => (theme.focus, theme.highlight),
+        false => (theme.border, theme.text),
     };
 
     let titles = app
@@ -87,25 +268,32 @@ pub fn tabs<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
         .collect();
 
     let tabs = Tabs::new(titles)
-        .block(Block::default().borders(Borders::ALL).title("Tabs"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title("Tabs"),
+        )
         .select(app.tabs.selected)
         .style(style)
         .highlight_style(highlight_style);
 
     frame.render_widget(tabs, area);
+    app.areas.tabs = area;
 }
 
 pub fn urlbar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
     let (method_style, _method_highlight_style) = match app.selected == Selected::MethodBar {
-        true => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-        false => (DEFAULT_STYLE, DEFAULT_STYLE),
+        true => (theme.focus, theme.highlight),
+        false => (theme.method(&app.urlbar.method), theme.text),
     };
 
     let (url_style, _url_highlight_style) =
         match (app.selected == Selected::Urlbar, app.urlbar.input_mode) {
-            (true, InputMode::Normal) => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-            (true, InputMode::Insert) => (INSERT_STYLE, HIGHLIGHT_STYLE),
-            (false, _) => (DEFAULT_STYLE, DEFAULT_STYLE),
+            (true, InputMode::Normal | InputMode::Visual) => (theme.focus, theme.highlight),
+            (true, InputMode::Insert) => (theme.insert, theme.highlight),
+            (false, _) => (theme.border, theme.text),
         };
 
     let chunks = Layout::default()
@@ -113,16 +301,29 @@ pub fn urlbar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
         .constraints([Constraint::Min(10), Constraint::Min(0)].as_ref())
         .split(area);
 
+    let mut title = vec![Span::raw(format!("URL: {}", app.urlbar.title))];
+    if app.skips_verification() {
+        title.push(Span::raw(" "));
+        title.push(Span::styled(" TLS NOT VERIFIED ", theme.warning));
+    }
+
     let block = Block::default()
-        .title(format!("URL: {}", app.urlbar.title))
+        .title(Line::from(title))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .style(url_style);
 
-    let text = Paragraph::new(app.urlbar.text.clone())
-        .block(block)
-        .wrap(Wrap { trim: true })
-        .alignment(Alignment::Left);
+    let urlbar = &app.urlbar;
+    let text = Paragraph::new(edited_lines(
+        &urlbar.text,
+        urlbar.input_mode,
+        &urlbar.vim,
+        urlbar.cursor_position,
+        &theme,
+    ))
+    .block(block)
+    .wrap(Wrap { trim: true })
+    .alignment(Alignment::Left);
 
     let method_block = Block::default()
         .borders(Borders::ALL)
@@ -138,26 +339,61 @@ pub fn urlbar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
     r.y += 1;
 
     frame.render_stateful_widget(menu, r, &mut app.urlbar.method_menu);
+    app.areas.method = chunks[0];
+    app.areas.url = chunks[1];
 
-    match app.urlbar.input_mode {
-        InputMode::Normal => {}
-
-        InputMode::Insert => frame.set_cursor(
-            chunks[1].x + app.urlbar.cursor_position as u16 + 1,
-            chunks[1].y + 1,
-        ),
+    if app.selected == Selected::Urlbar {
+        let urlbar = &app.urlbar;
+        let column = urlbar
+            .text
+            .get(..urlbar.cursor_position)
+            .map_or(urlbar.text.chars().count(), |text| text.chars().count());
+        frame.set_cursor(chunks[1].x + column as u16 + 1, chunks[1].y + 1);
     }
 }
 
+/// Lines of an edited text, with the selection highlighted in visual mode.
+fn edited_lines<'a>(
+    text: &'a str,
+    mode: InputMode,
+    vim: &Vim,
+    cursor: usize,
+    theme: &Theme,
+) -> Vec<Line<'a>> {
+    let selection = match mode {
+        InputMode::Visual => Some(vim.selection(text, cursor)),
+        _ => None,
+    };
+
+    let mut start = 0;
+    text.split('\n')
+        .map(|line| {
+            let end = start + line.len();
+            let range = selection.map(|(from, to)| (from.clamp(start, end), to.clamp(start, end)));
+            let offset = start;
+            start = end + 1;
+            match range {
+                Some((from, to)) if from < to => Line::from(vec![
+                    Span::raw(&line[..from - offset]),
+                    Span::styled(&line[from - offset..to - offset], theme.highlight),
+                    Span::raw(&line[to - offset..]),
+                ]),
+                _ => Line::from(line),
+            }
+        })
+        .collect()
+}
+
 pub fn requestbar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
     let (tab_style, tab_highlight_style) = match app.selected == Selected::RequestTab {
-        true => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-        false => (DEFAULT_STYLE, DEFAULT_STYLE),
+        true => (theme.focus, theme.highlight),
+        false => (theme.border, theme.text),
     };
 
     let (bar_style, _bar_highlight_style) = match app.selected == Selected::Requestbar {
-        true => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-        false => (DEFAULT_STYLE, DEFAULT_STYLE),
+        true => (theme.focus, theme.highlight),
+        false => (theme.border, theme.text),
     };
 
     let chunks = Layout::default()
@@ -180,37 +416,902 @@ pub fn requestbar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rec
         .highlight_style(tab_highlight_style);
 
     frame.render_widget(tabs, chunks[0]);
+    app.areas.request_tabs = chunks[0];
+    app.areas.request = chunks[1];
+
+    let title = match app.requestbar.disable_cookies {
+        true => "Request (cookies off)",
+        false => "Request",
+    };
 
     let block = Block::default()
-        .title("Request")
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .style(bar_style);
 
-    let text = Paragraph::new(app.requestbar.body.clone())
+    let area = match app.requestbar.request_menu {
+        RequestMenu::Tests => {
+            let test_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(8)].as_ref())
+                .split(chunks[1]);
+            tests(app, frame, test_chunks[1]);
+            test_chunks[0]
+        }
+        _ => chunks[1],
+    };
+
+    let requestbar = &app.requestbar;
+    let text = Paragraph::new(edited_lines(
+        requestbar.text().unwrap_or_default(),
+        requestbar.input_mode,
+        &requestbar.vim,
+        requestbar.cursor_position,
+        &theme,
+    ))
+    .block(block)
+    .wrap(Wrap { trim: false })
+    .scroll((app.requestbar.scroll, 0))
+    .alignment(Alignment::Left);
+
+    frame.render_widget(text, area);
+
+    if app.selected == Selected::Requestbar && app.requestbar.text().is_some() {
+        let (col, row) = app.requestbar.cursor();
+        if let Some(row) = row.checked_sub(app.requestbar.scroll) {
+            frame.set_cursor(area.x + col + 1, area.y + row + 1);
+        }
+    }
+}
+
+pub fn tests<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let lines: Vec<Line> = app
+        .requestbar
+        .test_results
+        .iter()
+        .map(|result| match (result.passed, &result.message) {
+            (true, _) => Line::from(Span::styled(
+                format!("PASS {}", result.assertion),
+                theme.pass,
+            )),
+            (false, message) => Line::from(Span::styled(
+                format!(
+                    "FAIL {}: {}",
+                    result.assertion,
+                    message.clone().unwrap_or_default()
+                ),
+                theme.fail,
+            )),
+        })
+        .collect();
+
+    let passed = app
+        .requestbar
+        .test_results
+        .iter()
+        .filter(|result| result.passed)
+        .count();
+
+    let block = Block::default()
+        .title(format!(
+            "Results {}/{}",
+            passed,
+            app.requestbar.test_results.len()
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.text);
+
+    let text = Paragraph::new(lines)
         .block(block)
-        .wrap(Wrap { trim: true })
-        .alignment(Alignment::Left);
+        .wrap(Wrap { trim: false });
 
-    frame.render_widget(text, chunks[1]);
+    frame.render_widget(text, area);
 }
 
 pub fn responsebar<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
-    let (style, _highlight_style) = match app.selected == Selected::Responsebar {
-        true => (SELECTED_STYLE, HIGHLIGHT_STYLE),
-        false => (DEFAULT_STYLE, DEFAULT_STYLE),
+    let theme = app.theme;
+    let (style, highlight_style) = match app.selected == Selected::Responsebar {
+        true => (theme.focus, theme.highlight),
+        false => (theme.border, theme.text),
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let titles = ResponseMenu::iter()
+        .map(|item| Line::from(item.to_string()))
+        .collect();
+
+    let idx = ResponseMenu::iter()
+        .position(|item| item == app.responsebar.response_menu)
+        .unwrap_or_default();
+
+    let tabs = Tabs::new(titles)
+        .select(idx)
+        .style(style)
+        .highlight_style(highlight_style);
+
+    frame.render_widget(tabs, chunks[0]);
+    app.areas.response_tabs = chunks[0];
+    app.areas.response = chunks[1];
+
+    let title = match (&app.pending, &app.responsebar.response) {
+        (Some(pending), _) => {
+            let received = pending.progress.received();
+            let seconds = pending.started.elapsed().as_secs_f64().max(0.001);
+            let size = match pending.progress.total() {
+                Some(total) => format!(
+                    "{} / {}",
+                    download::format_size(received),
+                    download::format_size(total)
+                ),
+                None => download::format_size(received),
+            };
+            Line::from(format!(
+                "Response: {} at {}/s (x: cancel)",
+                size,
+                download::format_size((received as f64 / seconds) as u64)
+            ))
+        }
+        (None, Some(res)) => {
+            let mut title = format!(
+                " in {}ms, {}",
+                res.elapsed.as_millis(),
+                download::format_size(res.raw.size)
+            );
+            if res.raw.is_truncated() {
+                title.push_str(&format!(
+                    " (showing {}, s: save all)",
+                    download::format_size(res.raw.preview.len() as u64)
+                ));
+            }
+            if !res.redirects.is_empty() {
+                title.push_str(&format!(" after {} redirect(s)", res.redirects.len()));
+            }
+            if let Some(proxy) = &res.proxy {
+                title.push_str(&format!(" via {}", proxy));
+            }
+            Line::from(vec![
+                Span::raw("Response: "),
+                Span::styled(res.status.to_string(), theme.status(res.status)),
+                Span::raw(title),
+            ])
+        }
+        (None, None) => Line::from("Response"),
     };
 
     let block = Block::default()
-        .title("Response")
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .style(style);
 
-    let text = Paragraph::new(app.responsebar.body.clone())
-        .block(block)
-        .wrap(Wrap { trim: true })
-        .alignment(Alignment::Left);
+    let responsebar = &app.responsebar;
+    let prompt = match (
+        &responsebar.save_path,
+        &responsebar.offset_input,
+        &responsebar.header_input,
+    ) {
+        (Some(path), _, _) => Some(format!("Save to: {}", path)),
+        (None, Some(offset), _) => Some(format!("Go to offset: {}", offset)),
+        (None, None, Some(name)) => Some(format!("Copy header: {}", name)),
+        (None, None, None) => None,
+    };
 
-    frame.render_widget(text, area);
+    let area = match prompt {
+        Some(prompt) => {
+            let save_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(chunks[1]);
+            frame.render_widget(
+                Paragraph::new(prompt.as_str()).style(theme.insert),
+                save_chunks[1],
+            );
+            frame.set_cursor(
+                save_chunks[1].x + prompt.chars().count() as u16,
+                save_chunks[1].y,
+            );
+            save_chunks[0]
+        }
+        None => chunks[1],
+    };
+
+    if let Some(pending) = &app.pending {
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if let Some(total) = pending.progress.total() {
+            let ratio = (pending.progress.received() as f64 / total as f64).min(1.0);
+            let gauge = Gauge::default().gauge_style(theme.focus).ratio(ratio);
+            frame.render_widget(
+                gauge,
+                Rect::new(inner.x, inner.y, inner.width, 1.min(inner.height)),
+            );
+        }
+        return;
+    }
+
+    match app.responsebar.response_menu {
+        ResponseMenu::Body => {
+            let responsebar = &app.responsebar;
+            let area = match responsebar.filtering || !responsebar.filter.is_empty() {
+                true => {
+                    let filter_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                        .split(area);
+                    let prompt = format!("Filter: {}", responsebar.filter);
+                    let mut spans = vec![Span::styled(
+                        prompt.clone(),
+                        match responsebar.filtering {
+                            true => theme.insert,
+                            false => theme.text,
+                        },
+                    )];
+                    if let Some(err) = &responsebar.filter_error {
+                        spans.push(Span::styled(format!("  {}", err), theme.warning));
+                    }
+                    frame.render_widget(Paragraph::new(Line::from(spans)), filter_chunks[0]);
+                    if responsebar.filtering {
+                        frame.set_cursor(
+                            filter_chunks[0].x + prompt.chars().count() as u16,
+                            filter_chunks[0].y,
+                        );
+                    }
+                    filter_chunks[1]
+                }
+                false => area,
+            };
+
+            let body: Vec<Line> = match &app.responsebar.response {
+                Some(res) if res.binary => vec![Line::from(format!(
+                    "Binary content ({}, {}), shown in the Hex tab",
+                    content::mime(&res.headers).unwrap_or_else(|| String::from("unknown type")),
+                    download::format_size(res.raw.size)
+                ))],
+                Some(_) => {
+                    let selected = responsebar.selected_lines();
+                    responsebar
+                        .formatted
+                        .iter()
+                        .enumerate()
+                        .skip(responsebar.body_scroll)
+                        .take(area.height as usize)
+                        .map(|(i, line)| {
+                            let style = match selected {
+                                Some((first, last)) if (first..=last).contains(&i) => {
+                                    theme.highlight
+                                }
+                                _ => Style::default(),
+                            };
+                            Line::from(
+                                line.iter()
+                                    .map(|(highlight, text)| {
+                                        Span::styled(
+                                            text.as_str(),
+                                            theme.syntax(*highlight).patch(style),
+                                        )
+                                    })
+                                    .collect::<Vec<_>>(),
+                            )
+                        })
+                        .collect()
+                }
+                None => app.responsebar.body.lines().map(Line::from).collect(),
+            };
+
+            let format = match (app.responsebar.format, app.responsebar.filter.is_empty()) {
+                _ if responsebar.selection.is_some() => {
+                    let (first, last) = responsebar.selected_lines().unwrap_or_default();
+                    format!("{} lines selected, y: copy, v: stop", last - first + 1)
+                }
+                (_, false) if app.responsebar.filter_error.is_none() => {
+                    String::from("filtered, /: edit filter")
+                }
+                (Some(format), _) => format!("{}, f: change, /: filter", format),
+                (None, _) => format!(
+                    "{} (auto), f: change, /: filter",
+                    app.responsebar.body_format()
+                ),
+            };
+            let block = match app.responsebar.response {
+                Some(_) => block.title(Title::from(format).alignment(Alignment::Right)),
+                None => block,
+            };
+
+            let text = Paragraph::new(body)
+                .block(block)
+                .wrap(Wrap { trim: false })
+                .alignment(Alignment::Left);
+
+            frame.render_widget(text, area);
+        }
+        ResponseMenu::Tree => match &mut app.responsebar.json {
+            Some(json) => {
+                let block = block
+                    .title(Title::from("y: copy value, Y: copy path").alignment(Alignment::Right));
+                let tree = Tree::new(json.tree.visible_items())
+                    .block(block)
+                    .highlight_style(highlight_style)
+                    .style(theme.text);
+                frame.render_stateful_widget(tree, area, &mut json.tree.state);
+            }
+            None => frame.render_widget(
+                Paragraph::new(format!("No tree, {}", app.responsebar.json_error)).block(block),
+                area,
+            ),
+        },
+        ResponseMenu::Hex => {
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+            hex(app, frame, inner);
+        }
+        ResponseMenu::Timeline => {
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+            timeline(app, frame, inner);
+        }
+    }
+}
+
+/// Hex dump of the response bytes, with offset, hex and ASCII columns.
+pub fn hex<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let responsebar = &app.responsebar;
+    let bytes = responsebar.bytes();
+
+    let lines: Vec<Line> = bytes
+        .chunks(HEX_WIDTH)
+        .enumerate()
+        .skip(responsebar.hex_scroll)
+        .take(area.height as usize)
+        .map(|(row, chunk)| {
+            let offset = row * HEX_WIDTH;
+            let style = |i: usize| match responsebar.hex_offset == Some(offset + i) {
+                true => theme.highlight,
+                false => theme.text,
+            };
+
+            let mut spans = vec![Span::styled(format!("{:08x}  ", offset), theme.focus)];
+            for i in 0..HEX_WIDTH {
+                let text = match chunk.get(i) {
+                    Some(byte) => format!("{:02x}", byte),
+                    None => String::from("  "),
+                };
+                spans.push(Span::styled(text, style(i)));
+                spans.push(Span::raw(match i {
+                    7 => "  ",
+                    _ => " ",
+                }));
+            }
+
+            spans.push(Span::raw(" |"));
+            for (i, byte) in chunk.iter().enumerate() {
+                let c = match byte.is_ascii_graphic() || *byte == b' ' {
+                    true => *byte as char,
+                    false => '.',
+                };
+                spans.push(Span::styled(c.to_string(), style(i)));
+            }
+            spans.push(Span::raw("|"));
+
+            Line::from(spans)
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// Waterfall of the redirect hops and timing phases of the last response.
+pub fn timeline<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let Some(res) = &app.responsebar.response else {
+        frame.render_widget(Paragraph::new("No response yet"), area);
+        return;
+    };
+
+    let last = Hop {
+        method: res.method.clone(),
+        url: res.url.clone(),
+        status: res.status,
+        location: None,
+        headers: res.headers.clone(),
+        timing: res.timing.clone(),
+    };
+    let hops: Vec<&Hop> = res.redirects.iter().chain(std::iter::once(&last)).collect();
+
+    let total = hops
+        .iter()
+        .map(|hop| hop.timing.total().as_secs_f64())
+        .sum::<f64>()
+        .max(f64::EPSILON);
+
+    const LABEL_WIDTH: u16 = 28;
+    let bar_width = area.width.saturating_sub(LABEL_WIDTH) as f64;
+    let bottom = area.y + area.height;
+    let mut y = area.y;
+    let mut offset = 0.0;
+
+    for hop in &hops {
+        if y >= bottom {
+            return;
+        }
+
+        let mut heading = vec![
+            Span::styled(hop.status.to_string(), theme.status(hop.status)),
+            Span::styled(format!(" {} {}", hop.method, hop.url), theme.highlight),
+        ];
+        if let Some(location) = &hop.location {
+            heading.push(Span::raw(format!(" -> {}", location)));
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(heading)),
+            Rect::new(area.x, y, area.width, 1),
+        );
+        y += 1;
+
+        // Connecting and the TLS handshake cannot be told apart from waiting for the response.
+        let phases = [
+            ("DNS", hop.timing.dns, theme.attribute),
+            (
+                "Connect+TLS+TTFB",
+                Some(hop.timing.first_byte),
+                theme.insert,
+            ),
+            ("Download", Some(hop.timing.download), theme.pass),
+        ];
+
+        for (name, duration, style) in phases {
+            if y >= bottom {
+                return;
+            }
+
+            let label = match duration {
+                Some(duration) => format!("  {:<16} {:>5}ms", name, duration.as_millis()),
+                None => format!("  {:<16} {:>7}", name, "reused"),
+            };
+            frame.render_widget(
+                Paragraph::new(label),
+                Rect::new(area.x, y, LABEL_WIDTH.min(area.width), 1),
+            );
+
+            // Each phase is a gauge starting where the previous one ended, filled up to its end.
+            let seconds = duration.unwrap_or_default().as_secs_f64();
+            let x = (offset / total * bar_width) as u16;
+            if duration.is_some() && x < bar_width as u16 {
+                let width = bar_width as u16 - x;
+                let ratio = (seconds / (total - offset).max(f64::EPSILON))
+                    .max(1.0 / width as f64)
+                    .min(1.0);
+                let gauge = Gauge::default()
+                    .gauge_style(style)
+                    .ratio(ratio)
+                    .label("")
+                    .use_unicode(true);
+                frame.render_widget(gauge, Rect::new(area.x + LABEL_WIDTH + x, y, width, 1));
+            }
+
+            offset += seconds;
+            y += 1;
+        }
+    }
+
+    // Headers of the redirect responses, below the waterfall.
+    let lines: Vec<Line> = res
+        .redirects
+        .iter()
+        .flat_map(|hop| {
+            std::iter::once(Line::from(Span::styled(
+                format!("{} {}", hop.status, hop.url),
+                theme.highlight,
+            )))
+            .chain(
+                hop.headers
+                    .iter()
+                    .map(|(name, value)| Line::from(format!("  {}: {}", name, value))),
+            )
+        })
+        .collect();
+
+    if y + 1 < bottom && !lines.is_empty() {
+        frame.render_widget(
+            Paragraph::new(lines),
+            Rect::new(area.x, y + 1, area.width, bottom - y - 1),
+        );
+    }
+}
+
+pub fn console<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let block = Block::default()
+        .title("Console")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.text);
+
+    let height = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = app
+        .console
+        .iter()
+        .skip(app.console.len().saturating_sub(height))
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub fn runner<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let Some(runnerbar) = &app.runner else {
+        return;
+    };
+    let runner = &runnerbar.runner;
+
+    let block = Block::default()
+        .title("Runner")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.focus);
+
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(0),
+                Constraint::Length(2),
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+
+    let options = Paragraph::new(vec![
+        Line::from(format!(
+            "{} request(s), {} iteration(s), {} data row(s), delay {}ms, stop on failure: {}",
+            runner.items.len(),
+            runner.options.iterations,
+            runner.options.data.len(),
+            runner.options.delay.as_millis(),
+            runner.options.stop_on_failure,
+        )),
+        Line::from(app.keymap.hint(
+            Context::Runner,
+            &[
+                (&[Action::StartRun], "run"),
+                (
+                    &[Action::MoreIterations, Action::FewerIterations],
+                    "iterations",
+                ),
+                (&[Action::LongerDelay, Action::ShorterDelay], "delay"),
+                (&[Action::ToggleStopOnFailure], "stop on failure"),
+                (&[Action::ExportSummary], "export"),
+                (&[Action::Cancel], "close"),
+            ],
+        )),
+    ])
+    .style(theme.text);
+    frame.render_widget(options, chunks[0]);
+
+    let rows = runner.results.iter().skip(runnerbar.scroll).map(|result| {
+        let style = match (result.skipped, result.passed) {
+            (true, _) => theme.text,
+            (false, true) => theme.pass,
+            (false, false) => theme.fail,
+        };
+        let status = match (result.status, &result.error) {
+            (_, Some(error)) => error.clone(),
+            (Some(status), None) => status.to_string(),
+            (None, None) => String::from("-"),
+        };
+
+        Row::new(vec![
+            match (result.skipped, result.passed) {
+                (true, _) => "SKIP",
+                (false, true) => "PASS",
+                (false, false) => "FAIL",
+            }
+            .to_string(),
+            result.iteration.to_string(),
+            format!("{}/{}", result.folder, result.name),
+            status,
+            format!("{}ms", result.elapsed_ms),
+        ])
+        .style(style)
+    });
+
+    let widths = [
+        Constraint::Length(4),
+        Constraint::Length(4),
+        Constraint::Percentage(60),
+        Constraint::Percentage(25),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows)
+        .header(Row::new(vec!["", "#", "Request", "Status", "Time"]).style(theme.highlight))
+        .widths(&widths)
+        .style(theme.text);
+    frame.render_widget(table, chunks[1]);
+
+    let summary = runner.summary();
+    let state = match (runner.is_running(), runner.is_finished()) {
+        (true, _) => "running",
+        (false, true) => "finished",
+        (false, false) => "ready",
+    };
+    let footer = Paragraph::new(vec![
+        Line::from(format!(
+            "{} {}/{}: {} passed, {} failed, average {}ms, total {}ms",
+            state,
+            summary.total,
+            runner.total(),
+            summary.passed,
+            summary.failed,
+            summary.average_ms,
+            summary.total_ms,
+        )),
+        Line::from(runnerbar.message.clone().unwrap_or_default()),
+    ])
+    .style(theme.text);
+    frame.render_widget(footer, chunks[2]);
+}
+
+pub fn cookies<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let Some(cookiebar) = &app.cookies else {
+        return;
+    };
+
+    let block = Block::default()
+        .title(format!("Cookies: {}", app.environment_name()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.focus);
+
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)].as_ref())
+        .split(inner);
+
+    let rows = cookiebar.cookies.iter().enumerate().map(|(i, cookie)| {
+        // Only the first cookie of each domain shows the domain.
+        let domain = match i > 0 && cookiebar.cookies[i - 1].domain == cookie.domain {
+            true => String::new(),
+            false => cookie.domain.clone(),
+        };
+        let style = match i == cookiebar.selected {
+            true => theme.highlight,
+            false => theme.text,
+        };
+
+        Row::new(vec![
+            domain,
+            cookie.path.clone(),
+            cookie.name.clone(),
+            cookie.value.clone(),
+            cookie.expires.clone(),
+        ])
+        .style(style)
+    });
+
+    let widths = [
+        Constraint::Percentage(20),
+        Constraint::Percentage(10),
+        Constraint::Percentage(20),
+        Constraint::Percentage(30),
+        Constraint::Percentage(20),
+    ];
+    let table = Table::new(rows)
+        .header(Row::new(vec!["Domain", "Path", "Name", "Value", "Expires"]).style(theme.highlight))
+        .widths(&widths)
+        .style(theme.text);
+    frame.render_widget(table, chunks[0]);
+
+    let footer = match &cookiebar.input {
+        Some(input) => vec![
+            Line::from(format!("> {}", input)),
+            Line::from("enter: save  esc: cancel"),
+        ],
+        None => vec![
+            Line::from(cookiebar.message.clone().unwrap_or_default()),
+            Line::from(app.keymap.hint(
+                Context::Cookies,
+                &[
+                    (&[Action::AddCookie], "add"),
+                    (&[Action::Activate], "edit"),
+                    (&[Action::DeleteCookie], "delete"),
+                    (&[Action::Cancel], "close"),
+                ],
+            )),
+        ],
+    };
+    frame.render_widget(Paragraph::new(footer).style(theme.text), chunks[1]);
+
+    if let Some(input) = &cookiebar.input {
+        frame.set_cursor(chunks[1].x + input.chars().count() as u16 + 2, chunks[1].y);
+    }
+}
+
+pub fn client_settings<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let Some(clientbar) = &app.client_settings else {
+        return;
+    };
+
+    let block = Block::default()
+        .title("Client settings")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.focus);
+
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)].as_ref())
+        .split(inner);
+
+    let rows = ClientField::iter().enumerate().map(|(i, field)| {
+        let value = match (&clientbar.input, i == clientbar.selected) {
+            (Some(input), true) => format!("> {}", input),
+            _ => clientbar.value(field),
+        };
+        let style = match i == clientbar.selected {
+            true => theme.highlight,
+            false => theme.text,
+        };
+
+        Row::new(vec![field.to_string(), value]).style(style)
+    });
+
+    let widths = [Constraint::Percentage(40), Constraint::Percentage(60)];
+    let table = Table::new(rows).widths(&widths).style(theme.text);
+    frame.render_widget(table, chunks[0]);
+
+    let help = match clientbar.input {
+        Some(_) => String::from("enter: set  esc: cancel"),
+        None => app.keymap.hint(
+            Context::ClientSettings,
+            &[
+                (&[Action::Activate], "change"),
+                (&[Action::Cancel], "apply and close"),
+            ],
+        ),
+    };
+    let footer = vec![
+        Line::from(clientbar.message.clone().unwrap_or_default()),
+        Line::from(help),
+    ];
+    frame.render_widget(Paragraph::new(footer).style(theme.text), chunks[1]);
+}
+
+pub fn palette<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let Some(palettebar) = &app.palette else {
+        return;
+    };
+
+    let block = Block::default()
+        .title("Commands")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.focus);
+
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let prompt = format!("> {}", palettebar.query);
+    frame.render_widget(Paragraph::new(prompt.as_str()).style(theme.text), chunks[0]);
+    frame.set_cursor(chunks[0].x + prompt.chars().count() as u16, chunks[0].y);
+
+    let rows = palettebar.matches.iter().map(|(i, positions)| {
+        let entry = &palettebar.entries[*i];
+        // Matched characters stand out from the rest of the label.
+        let label = entry
+            .label
+            .chars()
+            .enumerate()
+            .map(|(j, c)| match positions.contains(&j) {
+                true => Span::styled(c.to_string(), theme.insert.add_modifier(Modifier::BOLD)),
+                false => Span::raw(c.to_string()),
+            })
+            .collect::<Vec<_>>();
+
+        Row::new(vec![
+            Cell::from(Line::from(label)),
+            Cell::from(entry.kind()),
+            Cell::from(entry.keys.as_str()),
+        ])
+    });
+
+    let widths = [
+        Constraint::Percentage(60),
+        Constraint::Percentage(15),
+        Constraint::Percentage(25),
+    ];
+    let table = Table::new(rows)
+        .widths(&widths)
+        .style(theme.text)
+        .highlight_style(theme.highlight);
+    let mut state = TableState::default();
+    state.select(Some(palettebar.selected));
+    frame.render_stateful_widget(table, chunks[1], &mut state);
+}
+
+/// Key bindings of the focused pane and of those it falls back to.
+pub fn help<B: Backend>(app: &mut App, frame: &mut Frame<'_, B>, area: Rect) {
+    let theme = app.theme;
+    let mut lines = Vec::new();
+    for (context, bindings) in app.keymap.help(&app.contexts()) {
+        if bindings.is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(Span::styled(
+            context.to_string().replace('_', " "),
+            theme.focus.add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(
+            bindings
+                .into_iter()
+                .map(|(keys, action)| Line::from(format!("  {:<20} {}", keys, action.label()))),
+        );
+    }
+
+    let block = Block::default()
+        .title(match app.popup() {
+            Some(popup) => format!("Help: {}", popup.to_string().replace('_', " ")),
+            None => format!("Help: {}", app.selected),
+        })
+        .title(
+            Title::from(app.keymap.hint(
+                Context::Help,
+                &[
+                    (&[Action::Down, Action::Up], "scroll"),
+                    (&[Action::Cancel], "close"),
+                ],
+            ))
+            .alignment(Alignment::Right)
+            .position(Position::Bottom),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(theme.focus);
+
+    let height = block.inner(area).height as usize;
+    app.help_scroll = app
+        .help_scroll
+        .min(lines.len().saturating_sub(height) as u16);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(theme.text)
+            .scroll((app.help_scroll, 0)),
+        area,
+    );
 }
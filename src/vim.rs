@@ -0,0 +1,649 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{component::urlbar::InputMode, keymap::KeyChord};
+
+/// Edits kept for undo.
+const UNDO_LIMIT: usize = 100;
+
+/// Characters of the commands and motions of normal mode, and the digits of counts.
+const NORMAL_KEYS: &str = "hjklwbe0$dcyxpPuiaIAv123456789";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn key(self) -> char {
+        match self {
+            Operator::Delete => 'd',
+            Operator::Change => 'c',
+            Operator::Yank => 'y',
+        }
+    }
+}
+
+/// How much of the text between the cursor and the end of a motion it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Span {
+    /// Up to the end, without it.
+    Exclusive,
+    /// Up to the end, with the character there.
+    Inclusive,
+    /// The whole lines of the cursor and the end.
+    Lines,
+}
+
+/// Vim-style normal and visual mode editing of a text input.
+#[derive(Debug, Default)]
+pub struct Vim {
+    /// Count typed before a command.
+    pub count: Option<usize>,
+    /// Operator waiting for a motion, with the count typed before it.
+    operator: Option<(Operator, usize)>,
+    /// Where the selection started in visual mode.
+    pub anchor: usize,
+    register: String,
+    /// Whether the register holds whole lines.
+    linewise: bool,
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+}
+
+impl Vim {
+    /// The count and operator typed so far, like `2d3`.
+    pub fn pending(&self) -> String {
+        let mut pending = String::new();
+        if let Some((operator, count)) = self.operator {
+            if count > 1 {
+                pending.push_str(&count.to_string());
+            }
+            pending.push(operator.key());
+        }
+        if let Some(count) = self.count {
+            pending.push_str(&count.to_string());
+        }
+        pending
+    }
+
+    /// Forgets the undo history and pending keys, when another text is edited.
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Remembers the text before it is changed, for undo.
+    pub fn snapshot(&mut self, text: &str, cursor: usize) {
+        self.undo.push((text.to_string(), cursor));
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// The selected range in visual mode.
+    pub fn selection(&self, text: &str, cursor: usize) -> (usize, usize) {
+        let (start, end) = match self.anchor <= cursor {
+            true => (self.anchor, cursor),
+            false => (cursor, self.anchor),
+        };
+        (start.min(text.len()), next(text, end.min(text.len())))
+    }
+
+    /// Redoes the edits undone last, as many as the count typed before.
+    pub fn redo(&mut self, text: &mut String, cursor: &mut usize) {
+        let count = self.count.take().unwrap_or(1);
+        for _ in 0..count {
+            if let Some((redone, at)) = self.redo.pop() {
+                self.undo.push((std::mem::replace(text, redone), *cursor));
+                *cursor = at;
+            }
+        }
+        *cursor = clamp(text, *cursor);
+    }
+
+    /// Handles a key in normal or visual mode, returning whether it was a vim command.
+    ///
+    /// Keys with ctrl or alt are left to the key bindings, which bind redo.
+    pub fn handle(
+        &mut self,
+        key_event: KeyEvent,
+        text: &mut String,
+        cursor: &mut usize,
+        mode: &mut InputMode,
+    ) -> bool {
+        let visual = matches!(mode, InputMode::Visual);
+
+        if key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+
+        let c = match key_event.code {
+            KeyCode::Char(c) => c,
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down => 'j',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Esc if visual || self.count.is_some() || self.operator.is_some() => {
+                self.count = None;
+                self.operator = None;
+                *mode = InputMode::Normal;
+                *cursor = clamp(text, *cursor);
+                return true;
+            }
+            _ => return false,
+        };
+
+        if let Some(digit) = c.to_digit(10).filter(|d| *d > 0 || self.count.is_some()) {
+            self.count = Some(self.count.unwrap_or(0) * 10 + digit as usize);
+            return true;
+        }
+        let count = self.count.take().unwrap_or(1);
+
+        if let Some((operator, operator_count)) = self.operator.take() {
+            let count = count * operator_count;
+            let (target, span) = match c == operator.key() {
+                true => (
+                    motion(text, *cursor, 'j', count - 1).map_or(*cursor, |(target, _)| target),
+                    Span::Lines,
+                ),
+                // Like vim, `cw` changes to the end of the word.
+                false if operator == Operator::Change && c == 'w' => {
+                    match motion(text, *cursor, 'e', count) {
+                        Some(motion) => motion,
+                        None => return true,
+                    }
+                }
+                false => match motion(text, *cursor, c, count) {
+                    Some(motion) => motion,
+                    None => return true,
+                },
+            };
+
+            let (start, end) = range(text, *cursor, target, span);
+            self.apply(operator, start, end, span, text, cursor, mode);
+            return true;
+        }
+
+        if let Some((target, _)) = motion(text, *cursor, c, count) {
+            *cursor = match visual {
+                true => target.min(text.len()),
+                false => clamp(text, target),
+            };
+            return true;
+        }
+
+        if visual {
+            let (start, end) = self.selection(text, *cursor);
+            let operator = match c {
+                'd' | 'x' => Operator::Delete,
+                'c' => Operator::Change,
+                'y' => Operator::Yank,
+                'v' => {
+                    *mode = InputMode::Normal;
+                    return true;
+                }
+                _ => return false,
+            };
+            *mode = InputMode::Normal;
+            self.apply(operator, start, end, Span::Inclusive, text, cursor, mode);
+            return true;
+        }
+
+        match c {
+            'd' => self.operator = Some((Operator::Delete, count)),
+            'c' => self.operator = Some((Operator::Change, count)),
+            'y' => self.operator = Some((Operator::Yank, count)),
+            'x' => {
+                let end = motion(text, *cursor, 'l', count).map_or(*cursor, |(end, _)| end);
+                if end > *cursor {
+                    self.apply(
+                        Operator::Delete,
+                        *cursor,
+                        end,
+                        Span::Exclusive,
+                        text,
+                        cursor,
+                        mode,
+                    );
+                }
+            }
+            'p' | 'P' => self.paste(c == 'p', count, text, cursor),
+            'u' => {
+                for _ in 0..count {
+                    if let Some((undone, at)) = self.undo.pop() {
+                        self.redo.push((std::mem::replace(text, undone), *cursor));
+                        *cursor = at;
+                    }
+                }
+                *cursor = clamp(text, *cursor);
+            }
+            'i' | 'a' | 'I' | 'A' => {
+                self.snapshot(text, *cursor);
+                *cursor = match c {
+                    'a' => next(text, *cursor).min(line_end(text, *cursor)),
+                    'I' => line_start(text, *cursor),
+                    'A' => line_end(text, *cursor),
+                    _ => *cursor,
+                };
+                *mode = InputMode::Insert;
+            }
+            'v' => {
+                self.anchor = *cursor;
+                *mode = InputMode::Visual;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply(
+        &mut self,
+        operator: Operator,
+        start: usize,
+        end: usize,
+        span: Span,
+        text: &mut String,
+        cursor: &mut usize,
+        mode: &mut InputMode,
+    ) {
+        self.linewise = span == Span::Lines;
+        self.register = text[start..end].to_string();
+        if self.linewise {
+            self.register = format!("{}\n", self.register.trim_matches('\n'));
+        }
+
+        match operator {
+            Operator::Yank => *cursor = clamp(text, start),
+            Operator::Delete => {
+                self.snapshot(text, *cursor);
+                text.replace_range(start..end, "");
+                *cursor = clamp(text, start);
+            }
+            Operator::Change => {
+                self.snapshot(text, *cursor);
+                // Changing lines keeps an empty line to type in.
+                let end = match span {
+                    Span::Lines if text[start..end].ends_with('\n') => end - 1,
+                    _ => end,
+                };
+                let start = match span {
+                    Span::Lines if text[start..end].starts_with('\n') => start + 1,
+                    _ => start,
+                };
+                text.replace_range(start..end, "");
+                *cursor = start;
+                *mode = InputMode::Insert;
+            }
+        }
+    }
+
+    fn paste(&mut self, after: bool, count: usize, text: &mut String, cursor: &mut usize) {
+        if self.register.is_empty() {
+            return;
+        }
+        self.snapshot(text, *cursor);
+
+        let pasted = self.register.repeat(count);
+        if self.linewise {
+            match after {
+                true => {
+                    let at = line_end(text, *cursor);
+                    let pasted = format!("\n{}", pasted.trim_end_matches('\n'));
+                    text.insert_str(at, &pasted);
+                    *cursor = at + 1;
+                }
+                false => {
+                    let at = line_start(text, *cursor);
+                    text.insert_str(at, &pasted);
+                    *cursor = at;
+                }
+            }
+            return;
+        }
+
+        let at = match after && *cursor < line_end(text, *cursor) {
+            true => next(text, *cursor),
+            false => *cursor,
+        };
+        text.insert_str(at, &pasted);
+        *cursor = prev(text, at + pasted.len());
+    }
+}
+
+/// Whether normal mode takes the key before the key bindings see it.
+pub fn takes(key: &KeyChord) -> bool {
+    key.modifiers.is_empty()
+        && match key.code {
+            KeyCode::Char(c) => NORMAL_KEYS.contains(c),
+            code => matches!(
+                code,
+                KeyCode::Left
+                    | KeyCode::Right
+                    | KeyCode::Up
+                    | KeyCode::Down
+                    | KeyCode::Home
+                    | KeyCode::End
+            ),
+        }
+}
+
+/// Where a motion key moves the cursor to, `count` times.
+fn motion(text: &str, cursor: usize, c: char, count: usize) -> Option<(usize, Span)> {
+    let once = |cursor: usize| -> usize {
+        match c {
+            'h' if cursor > line_start(text, cursor) => prev(text, cursor),
+            'l' if cursor < line_end(text, cursor) => next(text, cursor),
+            'j' | 'k' => vertical(text, cursor, c == 'j'),
+            'w' => word_forward(text, cursor),
+            'b' => word_backward(text, cursor),
+            'e' => word_end(text, cursor),
+            _ => cursor,
+        }
+    };
+
+    let span = match c {
+        'h' | 'l' | 'w' | 'b' | '0' | '$' => Span::Exclusive,
+        'e' => Span::Inclusive,
+        'j' | 'k' => Span::Lines,
+        _ => return None,
+    };
+    let target = match c {
+        '0' => line_start(text, cursor),
+        '$' => line_end(text, cursor),
+        _ => (0..count).fold(cursor, |cursor, _| once(cursor)),
+    };
+    Some((target, span))
+}
+
+/// Byte range an operator covers from `cursor` to `target`.
+fn range(text: &str, cursor: usize, target: usize, span: Span) -> (usize, usize) {
+    let (start, end) = match cursor <= target {
+        true => (cursor, target),
+        false => (target, cursor),
+    };
+    match span {
+        Span::Exclusive => (start, end),
+        Span::Inclusive => (start, next(text, end)),
+        Span::Lines => {
+            let (start, end) = (line_start(text, start), line_end(text, end));
+            match (end < text.len(), start > 0) {
+                (true, _) => (start, end + 1),
+                (false, true) => (start - 1, end),
+                (false, false) => (start, end),
+            }
+        }
+    }
+}
+
+/// Keeps the cursor on a character of its line in normal mode.
+fn clamp(text: &str, cursor: usize) -> usize {
+    let cursor = cursor.min(text.len());
+    match cursor >= line_end(text, cursor) && cursor > line_start(text, cursor) {
+        true => prev(text, cursor),
+        false => cursor,
+    }
+}
+
+fn prev(text: &str, i: usize) -> usize {
+    text[..i]
+        .chars()
+        .next_back()
+        .map_or(0, |c| i - c.len_utf8())
+}
+
+fn next(text: &str, i: usize) -> usize {
+    text[i..].chars().next().map_or(i, |c| i + c.len_utf8())
+}
+
+fn line_start(text: &str, i: usize) -> usize {
+    text[..i].rfind('\n').map_or(0, |n| n + 1)
+}
+
+fn line_end(text: &str, i: usize) -> usize {
+    text[i..].find('\n').map_or(text.len(), |n| i + n)
+}
+
+fn vertical(text: &str, cursor: usize, down: bool) -> usize {
+    let start = line_start(text, cursor);
+    let column = text[start..cursor].chars().count();
+    let target = match down {
+        true => match line_end(text, cursor) {
+            end if end < text.len() => end + 1,
+            _ => return cursor,
+        },
+        false => match start {
+            0 => return cursor,
+            start => line_start(text, start - 1),
+        },
+    };
+    let end = line_end(text, target);
+    text[target..end]
+        .char_indices()
+        .nth(column)
+        .map_or(end, |(i, _)| target + i)
+}
+
+#[derive(PartialEq)]
+enum Class {
+    Space,
+    Word,
+    Punctuation,
+}
+
+fn class(c: char) -> Class {
+    match c {
+        c if c.is_whitespace() => Class::Space,
+        c if c.is_alphanumeric() || c == '_' => Class::Word,
+        _ => Class::Punctuation,
+    }
+}
+
+fn class_at(text: &str, i: usize) -> Option<Class> {
+    text[i..].chars().next().map(class)
+}
+
+fn word_forward(text: &str, cursor: usize) -> usize {
+    let mut i = cursor;
+    if let Some(start) = class_at(text, i).filter(|class| *class != Class::Space) {
+        while class_at(text, i).is_some_and(|class| class == start) {
+            i = next(text, i);
+        }
+    }
+    while class_at(text, i) == Some(Class::Space) {
+        i = next(text, i);
+    }
+    i
+}
+
+fn word_backward(text: &str, cursor: usize) -> usize {
+    let mut i = cursor;
+    while i > 0 && class_at(text, prev(text, i)) == Some(Class::Space) {
+        i = prev(text, i);
+    }
+    if let Some(word) = (i > 0).then(|| class_at(text, prev(text, i))).flatten() {
+        while i > 0 && class_at(text, prev(text, i)).as_ref() == Some(&word) {
+            i = prev(text, i);
+        }
+    }
+    i
+}
+
+fn word_end(text: &str, cursor: usize) -> usize {
+    let mut i = next(text, cursor);
+    while class_at(text, i) == Some(Class::Space) {
+        i = next(text, i);
+    }
+    let Some(word) = class_at(text, i) else {
+        return prev(text, text.len());
+    };
+    while class_at(text, next(text, i)).as_ref() == Some(&word) {
+        i = next(text, i);
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Text and cursor after typing `keys` in normal mode, with the editor to check further.
+    fn typed(text: &str, cursor: usize, keys: &str) -> (String, usize, InputMode, Vim) {
+        let (mut text, mut cursor) = (text.to_string(), cursor);
+        let (mut vim, mut mode) = (Vim::default(), InputMode::Normal);
+        for c in keys.chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            vim.handle(key, &mut text, &mut cursor, &mut mode);
+        }
+        (text, cursor, mode, vim)
+    }
+
+    fn moved(text: &str, cursor: usize, keys: &str) -> usize {
+        typed(text, cursor, keys).1
+    }
+
+    fn edited(text: &str, cursor: usize, keys: &str) -> (String, usize) {
+        let (text, cursor, _, _) = typed(text, cursor, keys);
+        (text, cursor)
+    }
+
+    #[test]
+    fn moves_along_the_line() {
+        assert_eq!(moved("hello", 2, "h"), 1);
+        assert_eq!(moved("hello", 2, "l"), 3);
+        assert_eq!(moved("hello", 2, "3l"), 4);
+        assert_eq!(moved("hello", 0, "h"), 0);
+        assert_eq!(moved("ab\ncd", 3, "h"), 3);
+        assert_eq!(moved("hello", 2, "0"), 0);
+        assert_eq!(moved("hello", 2, "$"), 4);
+        assert_eq!(moved("héllo", 0, "ll"), 3);
+    }
+
+    #[test]
+    fn moves_between_lines_keeping_the_column() {
+        let text = "first\nabcd\nthird";
+        assert_eq!(moved(text, 3, "j"), 9);
+        assert_eq!(moved(text, 3, "2j"), 14);
+        assert_eq!(moved(text, 14, "k"), 9);
+        assert_eq!(moved(text, 1, "k"), 1);
+        assert_eq!(moved("first\nab", 4, "j"), 7);
+    }
+
+    #[test]
+    fn moves_by_words() {
+        let text = "get /users?id=5 now";
+        assert_eq!(moved(text, 0, "w"), 4);
+        assert_eq!(moved(text, 4, "w"), 5);
+        assert_eq!(moved(text, 0, "3w"), 10);
+        assert_eq!(moved(text, 10, "b"), 5);
+        assert_eq!(moved(text, 5, "e"), 9);
+        assert_eq!(moved(text, 16, "e"), 18);
+    }
+
+    #[test]
+    fn deletes_changes_and_yanks_with_motions() {
+        assert_eq!(edited("one two three", 0, "dw"), ("two three".into(), 0));
+        assert_eq!(edited("one two three", 0, "2dw"), ("three".into(), 0));
+        assert_eq!(edited("one two three", 0, "d2w"), ("three".into(), 0));
+        assert_eq!(edited("one two three", 4, "d$"), ("one ".into(), 3));
+        assert_eq!(edited("one two", 4, "db"), ("two".into(), 0));
+
+        let (text, cursor, mode, _) = typed("one two", 0, "cw");
+        assert_eq!((text.as_str(), cursor), (" two", 0));
+        assert!(matches!(mode, InputMode::Insert));
+
+        let (text, cursor, mode, vim) = typed("one two", 4, "yb");
+        assert_eq!(
+            (text.as_str(), cursor, vim.register.as_str()),
+            ("one two", 0, "one ")
+        );
+        assert!(matches!(mode, InputMode::Normal));
+    }
+
+    #[test]
+    fn operates_on_whole_lines() {
+        assert_eq!(edited("a\nb\nc", 2, "dd"), ("a\nc".into(), 2));
+        assert_eq!(edited("a\nb\nc", 4, "dd"), ("a\nb".into(), 2));
+        assert_eq!(edited("a\nb\nc", 0, "2dd"), ("c".into(), 0));
+        assert_eq!(edited("a\nb\nc", 0, "dj"), ("c".into(), 0));
+        assert_eq!(edited("a\nb\nc", 2, "cc"), ("a\n\nc".into(), 2));
+        assert_eq!(edited("a\nb\nc", 0, "yyp"), ("a\na\nb\nc".into(), 2));
+        assert_eq!(edited("a\nb\nc", 2, "yyP"), ("a\nb\nb\nc".into(), 2));
+    }
+
+    #[test]
+    fn deletes_and_pastes_characters() {
+        assert_eq!(edited("hello", 1, "x"), ("hllo".into(), 1));
+        assert_eq!(edited("hello", 1, "3x"), ("ho".into(), 1));
+        assert_eq!(edited("hello", 4, "x"), ("hell".into(), 3));
+        assert_eq!(edited("hello", 0, "xp"), ("ehllo".into(), 1));
+        assert_eq!(edited("hello", 0, "xP"), ("hello".into(), 0));
+        assert_eq!(edited("ab", 0, "yl2p"), ("aaab".into(), 2));
+    }
+
+    #[test]
+    fn undoes_and_redoes_edits() {
+        assert_eq!(edited("hello", 0, "xxu"), ("ello".into(), 0));
+        assert_eq!(edited("hello", 0, "xx2u"), ("hello".into(), 0));
+
+        let (mut text, mut cursor, _, mut vim) = typed("hello", 0, "xxuu");
+        vim.redo(&mut text, &mut cursor);
+        assert_eq!(text, "ello");
+        vim.count = Some(2);
+        vim.redo(&mut text, &mut cursor);
+        assert_eq!(text, "llo");
+    }
+
+    #[test]
+    fn enters_insert_mode_where_asked() {
+        let inserted = |keys| {
+            let (_, cursor, mode, _) = typed("ab\ncd", 3, keys);
+            assert!(matches!(mode, InputMode::Insert));
+            cursor
+        };
+        assert_eq!(inserted("i"), 3);
+        assert_eq!(inserted("a"), 4);
+        assert_eq!(inserted("A"), 5);
+        assert_eq!(inserted("$I"), 3);
+    }
+
+    #[test]
+    fn operates_on_the_visual_selection() {
+        assert_eq!(edited("hello world", 0, "vlld"), ("lo world".into(), 0));
+        assert_eq!(edited("hello world", 4, "vhhx"), ("he world".into(), 2));
+
+        let (text, cursor, mode, vim) = typed("hello world", 6, "vey");
+        assert_eq!(
+            (text.as_str(), cursor, vim.register.as_str()),
+            ("hello world", 6, "world")
+        );
+        assert!(matches!(mode, InputMode::Normal));
+
+        let (_, _, mode, _) = typed("hello", 0, "vlv");
+        assert!(matches!(mode, InputMode::Normal));
+    }
+
+    #[test]
+    fn leaves_chords_to_the_key_bindings() {
+        let (mut text, mut cursor, mut mode) = (String::from("hello"), 0, InputMode::Normal);
+        let mut vim = Vim::default();
+        let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        assert!(!vim.handle(ctrl_x, &mut text, &mut cursor, &mut mode));
+        let alt_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::ALT);
+        assert!(!vim.handle(alt_d, &mut text, &mut cursor, &mut mode));
+        assert_eq!(text, "hello");
+        assert!(takes(&"x".parse().unwrap()));
+        assert!(!takes(&"ctrl-x".parse().unwrap()));
+        assert!(!takes(&"q".parse().unwrap()));
+    }
+}